@@ -132,10 +132,9 @@ impl Display {
 
         let dep_range = self.get_max_range()?;
 
-        let mut line_index = 1;
-        let mut index = dep_range.start;
-
-        for selected in &self.package_selector.data[dep_range] {
+        for (line_index, (index, selected)) in
+            (1..).zip((dep_range.start..).zip(&self.package_selector.data[dep_range]))
+        {
             if index == self.package_selector.selected_index {
                 self.term.move_cursor_to(0, line_index)?;
                 write!(self.term, ">")?;
@@ -143,9 +142,6 @@ impl Display {
 
             self.term.move_cursor_to(2, line_index)?;
             write!(self.term, "{}", selected.display_name())?;
-
-            index += 1;
-            line_index += 1;
         }
 
         Ok(())
@@ -157,10 +153,9 @@ impl Display {
 
         let dep_range = self.get_max_range()?;
 
-        let mut line_index = 1;
-        let mut index = dep_range.start;
-
-        for selector in &self.dep_selector.data[dep_range] {
+        for (line_index, (index, selector)) in
+            (1..).zip((dep_range.start..).zip(&self.dep_selector.data[dep_range]))
+        {
             if index == self.dep_selector.selected_index {
                 self.term.move_cursor_to(0, line_index)?;
                 write!(self.term, ">")?;
@@ -169,9 +164,6 @@ impl Display {
             self.term.move_cursor_to(2, line_index)?;
 
             write!(self.term, "{}", selector.display_name())?;
-
-            index += 1;
-            line_index += 1;
         }
 
         Ok(())
@@ -190,7 +182,6 @@ impl Display {
         let feature_range = self.get_max_range()?;
 
         let mut line_index = 1;
-        let mut index = feature_range.start;
 
         write!(self.term, "{} {}", dep.get_name(), dep.get_version())?;
 
@@ -205,7 +196,9 @@ impl Display {
                 self.dep_selector.get_selected()?.name()
             ))?;
 
-        for feature in &self.feature_selector.data[self.get_max_range()?] {
+        for (index, feature) in
+            (feature_range.start..).zip(&self.feature_selector.data[feature_range.clone()])
+        {
             let data = dep
                 .get_feature(feature.name())
                 .context(format!("couldn't find {}", feature.name()))?;
@@ -264,7 +257,6 @@ impl Display {
             }
 
             line_index += 1;
-            index += 1;
         }
 
         Ok(())
@@ -299,10 +291,8 @@ impl Display {
             (Key::ArrowUp, DisplayState::Dep) => {
                 self.dep_selector.shift(-1);
             }
-            (Key::ArrowUp, DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    self.feature_selector.shift(-1);
-                }
+            (Key::ArrowUp, DisplayState::Feature) if self.feature_selector.has_data() => {
+                self.feature_selector.shift(-1);
             }
             //down
             (Key::ArrowDown, DisplayState::Package) => {
@@ -311,72 +301,69 @@ impl Display {
             (Key::ArrowDown, DisplayState::Dep) => {
                 self.dep_selector.shift(1);
             }
-            (Key::ArrowDown, DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    self.feature_selector.shift(1);
-                }
+            (Key::ArrowDown, DisplayState::Feature) if self.feature_selector.has_data() => {
+                self.feature_selector.shift(1);
             }
 
             //selection
             (Key::Enter, DisplayState::Package)
             | (Key::ArrowRight, DisplayState::Package)
-            | (Key::Char(' '), DisplayState::Package) => {
-                if self.package_selector.has_data() {
-                    let name = self.package_selector.get_selected()?.name();
+            | (Key::Char(' '), DisplayState::Package)
+                if self.package_selector.has_data() =>
+            {
+                let name = self.package_selector.get_selected()?.name();
 
-                    if !self
-                        .document
-                        .get_package(name)
-                        .context(format!("package not found - {}", name))?
-                        .dependencies
-                        .is_empty()
-                    {
-                        self.search_text = "".to_string();
+                if !self
+                    .document
+                    .get_package(name)
+                    .context(format!("package not found - {}", name))?
+                    .dependencies
+                    .is_empty()
+                {
+                    self.search_text = "".to_string();
 
-                        self.select_selected_package()?;
+                    self.select_selected_package()?;
 
-                        //needed to wrap
-                        self.dep_selector.shift(0);
-                    }
+                    //needed to wrap
+                    self.dep_selector.shift(0);
                 }
             }
             (Key::Enter, DisplayState::Dep)
             | (Key::ArrowRight, DisplayState::Dep)
-            | (Key::Char(' '), DisplayState::Dep) => {
+            | (Key::Char(' '), DisplayState::Dep)
                 if self.dep_selector.has_data()
                     && self
                         .document
                         .get_package(self.package_selector.get_selected()?.name())?
                         .get_dep(self.dep_selector.get_selected()?.name())?
-                        .has_features()
-                {
-                    self.search_text = "".to_string();
+                        .has_features() =>
+            {
+                self.search_text = "".to_string();
 
-                    self.select_selected_dep()?;
+                self.select_selected_dep()?;
 
-                    //needed to wrap
-                    self.feature_selector.shift(0);
-                }
+                //needed to wrap
+                self.feature_selector.shift(0);
             }
             (Key::Enter, DisplayState::Feature)
             | (Key::ArrowRight, DisplayState::Feature)
-            | (Key::Char(' '), DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    let dep_name = self.dep_selector.get_selected()?.name();
+            | (Key::Char(' '), DisplayState::Feature)
+                if self.feature_selector.has_data() =>
+            {
+                let dep_name = self.dep_selector.get_selected()?.name();
 
-                    let dep = self
-                        .document
-                        .get_package_mut(self.package_selector.get_selected()?.name())?
-                        .get_dep_mut(dep_name)?;
+                let dep = self
+                    .document
+                    .get_package_mut(self.package_selector.get_selected()?.name())?
+                    .get_dep_mut(dep_name)?;
 
-                    dep.toggle_feature(self.feature_selector.get_selected()?.name())?;
+                dep.toggle_feature(self.feature_selector.get_selected()?.name())?;
 
-                    save_dependency(
-                        &mut self.document,
-                        self.package_selector.get_selected()?.name(),
-                        dep_name,
-                    )?;
-                }
+                save_dependency(
+                    &mut self.document,
+                    self.package_selector.get_selected()?.name(),
+                    dep_name,
+                )?;
             }
 
             //search
@@ -417,7 +404,7 @@ impl Display {
             DisplayState::Dep => self.dep_selector.selected_index,
             DisplayState::Feature => self.feature_selector.selected_index,
             DisplayState::Package => self.package_selector.selected_index,
-        } as isize;
+        };
 
         let max_range = match self.state {
             DisplayState::Dep => self.dep_selector.data.len(),
@@ -445,13 +432,12 @@ impl Display {
             }
         }
 
-        let height = self.term.size().0 as usize;
-
-        let start = (current_selected - height as isize / 2 + 1)
-            .min(max_range as isize - height as isize + 1 + offset as isize)
-            .max(0) as usize;
-
-        Ok(start..max_range.min(start + height - 1 - offset))
+        Ok(get_visible_range(
+            current_selected,
+            max_range,
+            self.term.size().0 as usize,
+            offset,
+        ))
     }
 
     fn update_selected_data(&mut self) -> Result<()> {
@@ -518,3 +504,19 @@ enum DisplayState {
     Dep,
     Feature,
 }
+
+/// the items which fit on a screen of the height below the header - keeps the selected item centered
+///
+/// `offset` lines are reserved below the items
+pub fn get_visible_range(
+    selected_index: usize,
+    item_count: usize,
+    height: usize,
+    offset: usize,
+) -> Range<usize> {
+    let start = (selected_index as isize - height as isize / 2 + 1)
+        .min(item_count as isize - height as isize + 1 + offset as isize)
+        .max(0) as usize;
+
+    start..item_count.min(start + height - 1 - offset)
+}
@@ -45,6 +45,27 @@ impl FilterViewItem {
         }
     }
 
+    /// a feature prune wants to disable - the package is only shown in workspaces
+    pub fn from_removal(
+        package_name: &str,
+        dependency_name: &str,
+        feature_name: &str,
+        show_package: bool,
+    ) -> Self {
+        let name = format!("{dependency_name}/{feature_name}");
+        let mut display_name = name.clone();
+
+        if show_package {
+            display_name.push_str(
+                &style(format!(" ({})", package_name))
+                    .color256(8)
+                    .to_string(),
+            );
+        }
+
+        Self { name, display_name }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
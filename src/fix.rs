@@ -0,0 +1,393 @@
+use crate::io::save::save_dependency;
+use crate::project::dependency::DependencyType;
+use crate::project::document::Document;
+use crate::prune::{DependencyName, FeatureName, INTERRUPTED, PackageName};
+use cargo_metadata::diagnostic::Diagnostic;
+use cargo_metadata::{Message, Metadata, Target};
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, bail};
+use console::{Term, style};
+use indicatif::ProgressBar;
+use std::io::{BufReader, Write};
+use std::ops::Not;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// rustc points to the feature an item is gated behind when it was configured out
+struct Hint {
+    package_name: PackageName,
+    target: Target,
+    /// the file of the configured out item - inside of the crate which defines the feature
+    file_name: String,
+    feature: FeatureName,
+}
+
+/// enables the features rustc points to until the project builds
+pub fn fix() -> Result<()> {
+    let mut document = Document::new(".")?;
+    let term = Term::stdout();
+
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .current_dir(document.root_path())
+        .exec()?;
+
+    loop {
+        let Some(hints) = check(document.root_path(), &metadata)? else {
+            writeln!(&term, "the project builds")?;
+            return Ok(());
+        };
+
+        for (package_name, dependency_name, feature) in
+            apply_hints(&mut document, &metadata, &hints)?
+        {
+            writeln!(
+                &term,
+                "{} {} [{}]",
+                package_name,
+                dependency_name,
+                style(format!("+{feature}")).green()
+            )?;
+        }
+    }
+}
+
+/// enables the features the hints point to and saves the manifests - returns the enabled features
+///
+/// fails if none of the hints points to a missing feature, as the next check would fail the same way
+fn apply_hints(
+    document: &mut Document,
+    metadata: &Metadata,
+    hints: &[Hint],
+) -> Result<Vec<(PackageName, DependencyName, FeatureName)>> {
+    let mut enabled = vec![];
+
+    for hint in hints {
+        // several errors can point to the same feature - it is enabled by the first one
+        let Some((dependency_name, feature)) = resolve_hint(document, metadata, hint) else {
+            continue;
+        };
+
+        document
+            .get_package_mut(&hint.package_name)?
+            .get_dep_mut(&dependency_name)?
+            .enable_feature(&feature)?;
+
+        save_dependency(document, &hint.package_name, &dependency_name)?;
+
+        enabled.push((hint.package_name.clone(), dependency_name, feature));
+    }
+
+    if enabled.is_empty() {
+        bail!("the project does not build - rustc does not point to any missing feature");
+    }
+
+    Ok(enabled)
+}
+
+/// runs `cargo check` - returns the hints if it failed
+fn check(path: &Path, metadata: &Metadata) -> Result<Option<Vec<Hint>>> {
+    let progress = ProgressBar::new_spinner()
+        .with_message(format!("running '{}'", style("cargo check").dim()));
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    let mut child = Command::new("cargo")
+        .current_dir(path)
+        .args([
+            "check",
+            "--workspace",
+            "--all-targets",
+            "--message-format=json",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("could not read the output of cargo")?;
+
+    let mut hints = vec![];
+
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        let Message::CompilerMessage(message) = message? else {
+            continue;
+        };
+
+        let Some(package) = metadata
+            .packages
+            .iter()
+            .find(|package| package.id == message.package_id)
+        else {
+            continue;
+        };
+
+        for (file_name, feature) in gated_features(&message.message) {
+            hints.push(Hint {
+                package_name: package.name.to_string(),
+                target: message.target.clone(),
+                file_name,
+                feature,
+            });
+        }
+    }
+
+    let status = child.wait()?;
+
+    progress.finish_and_clear();
+
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        bail!("interrupted");
+    }
+
+    Ok(status.success().not().then_some(hints))
+}
+
+/// the files and features of every configured out item the diagnostic mentions
+fn gated_features(diagnostic: &Diagnostic) -> Vec<(String, FeatureName)> {
+    let mut features = vec![];
+    let mut file_name = None;
+
+    // the feature is either a label of the "found an item that was configured out" note or a note of its own
+    for child in &diagnostic.children {
+        if let Some(span) = child.spans.iter().find(|span| span.is_primary) {
+            file_name = Some(span.file_name.clone());
+        }
+
+        let labels = child.spans.iter().filter_map(|span| span.label.as_deref());
+
+        for text in [child.message.as_str()].into_iter().chain(labels) {
+            if let (Some(file_name), Some(feature)) = (&file_name, gated_feature(text)) {
+                features.push((file_name.clone(), feature.to_string()));
+            }
+        }
+    }
+
+    features
+}
+
+/// e.g. "the item is gated behind the `derive` feature"
+fn gated_feature(text: &str) -> Option<&str> {
+    let (_, rest) = text.split_once("gated behind the `")?;
+    let (feature, rest) = rest.split_once('`')?;
+
+    rest.starts_with(" feature").then_some(feature)
+}
+
+/// the dependency of the package which defines the feature - if it is not enabled already
+fn resolve_hint(
+    document: &Document,
+    metadata: &Metadata,
+    hint: &Hint,
+) -> Option<(DependencyName, FeatureName)> {
+    // the files of workspace members are relative to the workspace root
+    let file = metadata.workspace_root.as_std_path().join(&hint.file_name);
+
+    // the crate whose directory is the closest to the file
+    let crate_name = metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            package
+                .manifest_path
+                .parent()
+                .is_some_and(|dir| file.starts_with(dir))
+        })
+        .max_by_key(|package| package.manifest_path.as_str().len())?
+        .name
+        .to_string();
+
+    let is_build_script = hint.target.is_custom_build();
+    // tests, examples and benches
+    let is_dev_target = (is_build_script
+        || hint.target.is_lib()
+        || hint.target.is_bin()
+        || hint.target.is_proc_macro())
+    .not();
+
+    let dependency = document
+        .get_package(&hint.package_name)
+        .ok()?
+        .get_deps()
+        .iter()
+        .filter(|dependency| dependency.name == crate_name)
+        .filter(|dependency| match dependency.kind {
+            DependencyType::Normal | DependencyType::Workspace => is_build_script.not(),
+            DependencyType::Development => is_dev_target,
+            DependencyType::Build => is_build_script,
+            DependencyType::Unknown => false,
+        })
+        // a dev-dependency is only used by the targets which need it
+        .min_by_key(|dependency| matches!(dependency.kind, DependencyType::Development).not())?;
+
+    let feature = dependency.features.get(&hint.feature)?;
+
+    feature
+        .is_enabled()
+        .not()
+        .then(|| (dependency.get_name(), hint.feature.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::fix::{Hint, apply_hints, gated_feature, resolve_hint};
+    use crate::io::util::toml_document_from_path;
+    use crate::project::document::Document;
+    use crate::project::fixture::app_with_dep;
+    use cargo_metadata::{Metadata, MetadataCommand};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// `app` with the given dependencies and an integration test
+    fn project(dependencies: &str) -> (TempDir, Document, Metadata) {
+        let dir = app_with_dep(dependencies);
+        let app = dir.path().join("app");
+
+        fs::create_dir_all(app.join("tests")).unwrap();
+        fs::write(app.join("tests/it.rs"), "").unwrap();
+
+        let document = Document::new(&app).unwrap();
+        let metadata = MetadataCommand::new().current_dir(&app).exec().unwrap();
+
+        (dir, document, metadata)
+    }
+
+    /// a hint of the target of `app` with the given kind for an item in the sources of `dep`
+    fn hint(dir: &Path, metadata: &Metadata, target_kind: &str, feature: &str) -> Hint {
+        let target = metadata
+            .packages
+            .iter()
+            .find(|package| package.name.as_str() == "app")
+            .unwrap()
+            .targets
+            .iter()
+            .find(|target| {
+                target
+                    .kind
+                    .iter()
+                    .any(|kind| kind.to_string() == target_kind)
+            })
+            .unwrap()
+            .clone();
+
+        Hint {
+            package_name: "app".to_string(),
+            target,
+            // dependencies outside of the workspace have absolute paths
+            file_name: dir.join("dep/src/lib.rs").to_string_lossy().to_string(),
+            feature: feature.to_string(),
+        }
+    }
+
+    #[test]
+    fn renamed_dependencies_are_resolved() {
+        let (dir, document, metadata) = project(
+            r#"[dependencies]
+renamed = { package = "dep", path = "../dep" }"#,
+        );
+
+        let name = document.get_package("app").unwrap().get_deps()[0].get_name();
+
+        assert_eq!(
+            resolve_hint(
+                &document,
+                &metadata,
+                &hint(dir.path(), &metadata, "lib", "b")
+            ),
+            Some((name, "b".to_string()))
+        );
+    }
+
+    #[test]
+    fn unknown_and_enabled_features_are_not_resolved() {
+        let (dir, document, metadata) = project(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+
+        for feature in ["c", "a", "b"] {
+            assert_eq!(
+                resolve_hint(
+                    &document,
+                    &metadata,
+                    &hint(dir.path(), &metadata, "lib", feature)
+                ),
+                None,
+                "{feature}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_dependency_of_the_target_is_edited() {
+        let (dir, mut document, metadata) = project(
+            r#"[dependencies]
+dep = { path = "../dep", default-features = false }
+
+[dev-dependencies]
+dep = { path = "../dep", default-features = false }"#,
+        );
+
+        let hints = [
+            hint(dir.path(), &metadata, "test", "b"),
+            // the same feature once more e.g. for another error
+            hint(dir.path(), &metadata, "test", "b"),
+            hint(dir.path(), &metadata, "lib", "a"),
+        ];
+
+        let enabled = apply_hints(&mut document, &metadata, &hints).unwrap();
+        assert_eq!(enabled.len(), 2);
+
+        let manifest = toml_document_from_path(dir.path().join("app/Cargo.toml")).unwrap();
+        let dependency = &manifest["dependencies"]["dep"];
+        let dev_dependency = &manifest["dev-dependencies"]["dep"];
+
+        // `a` is the default feature
+        assert!(dependency.get("default-features").is_none());
+        assert!(dependency.get("features").is_none());
+
+        assert_eq!(dev_dependency["default-features"].as_bool(), Some(false));
+        assert_eq!(dev_dependency["features"].to_string().trim(), r#"["b"]"#);
+    }
+
+    #[test]
+    fn hints_without_a_missing_feature_are_an_error() {
+        let (dir, mut document, metadata) = project(
+            r#"[dependencies]
+dep = { path = "../dep" }"#,
+        );
+        let manifest_path = dir.path().join("app/Cargo.toml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap();
+
+        // `a` is enabled by default and `c` does not exist
+        let hints = [
+            hint(dir.path(), &metadata, "lib", "a"),
+            hint(dir.path(), &metadata, "lib", "c"),
+        ];
+
+        assert!(apply_hints(&mut document, &metadata, &hints).is_err());
+        assert_eq!(fs::read_to_string(&manifest_path).unwrap(), manifest);
+    }
+
+    #[test]
+    fn gated_feature_is_parsed() {
+        assert_eq!(
+            gated_feature("the item is gated behind the `derive` feature"),
+            Some("derive")
+        );
+        assert_eq!(
+            gated_feature("the item is gated behind the `use_alloc` feature"),
+            Some("use_alloc")
+        );
+        assert_eq!(gated_feature("found an item that was configured out"), None);
+        assert_eq!(
+            gated_feature("the item is gated behind the `a` target"),
+            None
+        );
+    }
+}
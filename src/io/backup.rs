@@ -0,0 +1,140 @@
+use crate::project::document::Document;
+use color_eyre::Result;
+use itertools::Itertools;
+use std::fs;
+use std::path::PathBuf;
+
+/// the content of every manifest and the Cargo.lock of a project before it got edited
+///
+/// the files are restored once the backup is dropped - e.g. after an error, a panic or ctrl-c - unless it was kept
+pub struct ManifestBackup {
+    /// `None` if the file did not exist
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    is_kept: bool,
+}
+
+impl ManifestBackup {
+    pub fn new(document: &Document) -> Result<Self> {
+        let mut paths = document
+            .get_packages()
+            .iter()
+            .map(|package| PathBuf::from(&package.manifest_path))
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        paths.push(document.root_path().join("Cargo.lock"));
+
+        let mut files = vec![];
+
+        for path in paths {
+            let content = match fs::read(&path) {
+                Ok(content) => Some(content),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+
+            files.push((path, content));
+        }
+
+        Ok(Self {
+            files,
+            is_kept: false,
+        })
+    }
+
+    pub fn restore(&self) -> Result<()> {
+        for (path, content) in &self.files {
+            match content {
+                Some(content) => fs::write(path, content)?,
+                None if path.exists() => fs::remove_file(path)?,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the files are not restored anymore
+    pub fn keep(mut self) {
+        self.is_kept = true;
+    }
+}
+
+impl Drop for ManifestBackup {
+    fn drop(&mut self) {
+        if self.is_kept {
+            return;
+        }
+
+        if let Err(err) = self.restore() {
+            eprintln!("could not restore the manifests: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::io::backup::ManifestBackup;
+    use crate::project::document::Document;
+    use crate::project::fixture::app_with_dep;
+    use std::fs;
+    use std::ops::Not;
+
+    const MANIFEST: &str = r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }"#;
+
+    #[test]
+    fn dropping_restores_the_original_files() {
+        let dir = app_with_dep(MANIFEST);
+        let app = dir.path().join("app");
+
+        let document = Document::new(&app).unwrap();
+        let manifest = fs::read(app.join("Cargo.toml")).unwrap();
+        let lock = fs::read(app.join("Cargo.lock")).unwrap();
+
+        let backup = ManifestBackup::new(&document).unwrap();
+
+        fs::write(app.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(app.join("Cargo.lock"), "").unwrap();
+
+        drop(backup);
+
+        assert_eq!(fs::read(app.join("Cargo.toml")).unwrap(), manifest);
+        assert_eq!(fs::read(app.join("Cargo.lock")).unwrap(), lock);
+    }
+
+    #[test]
+    fn a_missing_lock_file_is_removed_again() {
+        let dir = app_with_dep(MANIFEST);
+        let app = dir.path().join("app");
+
+        let document = Document::new(&app).unwrap();
+        fs::remove_file(app.join("Cargo.lock")).unwrap();
+
+        let backup = ManifestBackup::new(&document).unwrap();
+        fs::write(app.join("Cargo.lock"), "").unwrap();
+        drop(backup);
+
+        assert!(app.join("Cargo.lock").exists().not());
+    }
+
+    #[test]
+    fn kept_backups_do_not_restore() {
+        let dir = app_with_dep(MANIFEST);
+        let app = dir.path().join("app");
+
+        let document = Document::new(&app).unwrap();
+        let backup = ManifestBackup::new(&document).unwrap();
+
+        fs::write(app.join("Cargo.toml"), "[package]").unwrap();
+
+        backup.keep();
+
+        assert_eq!(
+            fs::read_to_string(app.join("Cargo.toml")).unwrap(),
+            "[package]"
+        );
+    }
+}
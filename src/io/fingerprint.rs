@@ -0,0 +1,63 @@
+use color_eyre::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// a hash over the content of all files which stays the same between runs and versions
+///
+/// missing files are hashed as empty
+pub fn fingerprint<P: AsRef<Path>>(paths: &[P]) -> Result<String> {
+    let mut hash = FNV_OFFSET;
+
+    for path in paths {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+
+        hash = hash_bytes(hash, path.as_ref().to_string_lossy().bytes().chain(content));
+    }
+
+    Ok(format!("{hash:016x}"))
+}
+
+/// the same hash as `fingerprint` over a text instead of files
+pub fn fingerprint_text(text: &str) -> String {
+    format!("{:016x}", hash_bytes(FNV_OFFSET, text.bytes()))
+}
+
+fn hash_bytes(mut hash: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash
+}
+
+/// all files inside the directory sorted by their path - skipping directories for which `skip` returns true
+pub fn files_in<P: AsRef<Path>>(dir: P, skip: &dyn Fn(&str) -> bool) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            if skip(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+
+            files.extend(files_in(&path, skip)?);
+        } else {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
@@ -1,3 +1,5 @@
+pub mod backup;
+pub mod fingerprint;
 pub mod parsing;
 pub mod save;
 pub mod util;
@@ -57,6 +57,7 @@ pub fn parse_dependency(
             .to_string()
             .trim_start_matches('^')
             .to_owned(),
+        resolved_version: Some(package.version.to_string()),
         kind,
         workspace,
         features: HashMap::new(),
@@ -128,6 +129,7 @@ pub fn parse_dependency_from_item(
         rename,
         comment: None,
         version: version.to_string(),
+        resolved_version: None,
         workspace: false,
         kind: DependencyType::Workspace,
         target: None,
@@ -135,6 +137,8 @@ pub fn parse_dependency_from_item(
     };
 
     if let Ok(package) = get_package_from_version(name, &VersionReq::parse(version)?, packages) {
+        dependency.resolved_version = Some(package.version.to_string());
+
         set_features(
             &mut dependency,
             package,
@@ -63,6 +63,12 @@ pub fn parse_package(
     Ok(Package {
         dependencies: dependencies?,
         name: package.name.to_string(),
+        features: package
+            .features
+            .keys()
+            .filter(|feature| *feature != "default")
+            .cloned()
+            .collect(),
         manifest_path: package.manifest_path.to_string(),
     })
 }
@@ -35,6 +35,7 @@ pub fn parse_workspace(
     let package = Package {
         dependencies: dependencies?,
         name: format!("{} Workspace", Emoji("🗃️", "")).to_string(),
+        features: vec![],
         manifest_path: path,
     };
 
@@ -2,7 +2,7 @@ use crate::io::util::{get_mut_dependecy_item_from_doc, toml_document_from_path};
 use crate::project::document::Document;
 use color_eyre::eyre::{ContextCompat, Error};
 use std::fs;
-use toml_edit::{Array, Formatted, InlineTable, Item, Value};
+use toml_edit::{Array, Formatted, InlineTable, Item, Table, Value};
 
 pub fn save_dependency(
     document: &mut Document,
@@ -106,3 +106,174 @@ pub fn save_dependency(
 
     fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
 }
+
+/// removes the entry of the dependency from the Cargo.toml of the package
+pub fn remove_dependency(
+    document: &Document,
+    package_name: &str,
+    dep_name: &str,
+) -> color_eyre::Result<()> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dep_name)?;
+
+    let mut doc = toml_document_from_path(&package.manifest_path)?;
+    let deps = get_mut_dependecy_item_from_doc(&dependency.kind, &dependency.target, &mut doc)?;
+
+    let deps = deps.as_table_like_mut().context(format!(
+        "could not parse dependencies as a table - {}",
+        package.name
+    ))?;
+
+    deps.remove(dependency.rename.as_ref().unwrap_or(&dependency.name))
+        .context("dependency not found")?;
+
+    fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
+}
+
+/// adds the feature to `cargo-features-manager.keep` of the package so prune keeps it in the future
+pub fn save_kept_feature(
+    document: &Document,
+    package_name: &str,
+    dep_name: &str,
+    feature_name: &str,
+) -> color_eyre::Result<()> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dep_name)?;
+
+    let mut doc = toml_document_from_path(&package.manifest_path)?;
+
+    let mut config = Table::new();
+    config.set_implicit(true);
+
+    let keep = doc
+        .entry("cargo-features-manager")
+        .or_insert(Item::Table(config))
+        .as_table_like_mut()
+        .context("could not parse cargo-features-manager as a table")?
+        .entry("keep")
+        .or_insert(Item::Table(Table::new()))
+        .as_table_like_mut()
+        .context("could not parse cargo-features-manager.keep as a table")?;
+
+    let features = keep
+        .entry(&dependency.name)
+        .or_insert(Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .context(format!(
+            "could not parse the kept features of {} as an array",
+            dependency.name
+        ))?;
+
+    if features
+        .iter()
+        .all(|feature| feature.as_str() != Some(feature_name))
+    {
+        features.push(feature_name);
+    }
+
+    fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::io::save::{remove_dependency, save_dependency, save_kept_feature};
+    use crate::io::util::toml_document_from_path;
+    use crate::project::document::Document;
+    use crate::project::fixture::{app_with_dep, workspace_with_dep};
+    use std::fs;
+    use std::ops::Not;
+
+    #[test]
+    fn kept_features_are_added_once() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep" }"#,
+        );
+        let manifest_path = dir.path().join("app/Cargo.toml");
+
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        save_kept_feature(&document, "app", "dep", "a").unwrap();
+        save_kept_feature(&document, "app", "dep", "b").unwrap();
+        save_kept_feature(&document, "app", "dep", "a").unwrap();
+
+        let manifest = fs::read_to_string(manifest_path).unwrap();
+
+        assert!(manifest.contains("dep = { path = \"../dep\" }"));
+        assert!(manifest.contains("[cargo-features-manager.keep]\ndep = [\"a\", \"b\"]\n"));
+    }
+
+    #[test]
+    fn workspace_features_are_written_to_the_root_manifest() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", features = ["b"] }"#,
+            r#"{ workspace = true }"#,
+        );
+        let member_manifest = fs::read_to_string(dir.path().join("app/Cargo.toml")).unwrap();
+
+        let mut document = Document::new(dir.path()).unwrap();
+        let workspace_name = document
+            .get_workspace_package()
+            .unwrap()
+            .unwrap()
+            .name
+            .clone();
+
+        let dependency = document
+            .get_package_mut(&workspace_name)
+            .unwrap()
+            .get_dep_mut("dep")
+            .unwrap();
+        dependency.disable_feature("a").unwrap();
+        dependency.disable_feature("b").unwrap();
+
+        save_dependency(&mut document, &workspace_name, "dep").unwrap();
+
+        let root_manifest = toml_document_from_path(dir.path().join("Cargo.toml")).unwrap();
+        let dep = &root_manifest["workspace"]["dependencies"]["dep"];
+
+        assert_eq!(dep["default-features"].as_bool(), Some(false));
+        assert!(dep.get("features").is_none());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("app/Cargo.toml")).unwrap(),
+            member_manifest
+        );
+    }
+
+    #[test]
+    fn dependencies_are_removed_from_their_table() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }
+
+[target.'cfg(unix)'.dependencies]
+dep = { path = "../dep" }
+
+[dev-dependencies.dep]
+path = "../dep"
+"#,
+        );
+        let manifest_path = dir.path().join("app/Cargo.toml");
+
+        let document = Document::new(dir.path().join("app")).unwrap();
+        let package = document.get_package("app").unwrap();
+        assert_eq!(package.get_deps().len(), 3);
+
+        for dependency in package.get_deps() {
+            remove_dependency(&document, "app", &dependency.get_name()).unwrap();
+        }
+
+        let manifest = toml_document_from_path(&manifest_path).unwrap();
+
+        assert!(manifest["dependencies"].as_table().unwrap().is_empty());
+        assert!(
+            manifest["target"]["cfg(unix)"]["dependencies"]
+                .as_table()
+                .unwrap()
+                .is_empty()
+        );
+        // the empty `[dev-dependencies]` of the `[dev-dependencies.dep]` table is implicit
+        assert!(manifest.to_string().contains("../dep").not());
+    }
+}
@@ -3,6 +3,7 @@ use color_eyre::eyre::{ContextCompat, bail, eyre};
 use std::fs;
 use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::project::dependency::DependencyType;
 
@@ -140,15 +141,109 @@ fn get_dependency_path(kind: &DependencyType, target: &Option<Platform>) -> Stri
     path.to_string()
 }
 
+/// parses durations like `90`, `90s`, `10m`, `2h` or `500ms` - a plain number is in seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+
+    let split = value
+        .find(|char: char| !char.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration \"{value}\" - expected e.g. 90s, 10m or 2h"))?;
+
+    let secs = |factor: u64| {
+        amount
+            .checked_mul(factor)
+            .map(Duration::from_secs)
+            .ok_or(format!("duration \"{value}\" is too long"))
+    };
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => secs(1),
+        "m" => secs(60),
+        "h" => secs(60 * 60),
+        _ => Err(format!(
+            "invalid duration unit \"{unit}\" - expected one of ms, s, m or h"
+        )),
+    }
+}
+
+/// matches `text` against a glob `pattern` where `*` matches any number of characters and `?` a single one
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut pattern_index, mut text_index) = (0, 0);
+    // the last `*` and the text position it currently matches up to
+    let mut backtrack = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(char) if *char == '?' || *char == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, text_index));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|char| *char == '*')
+}
+
+/// formats a byte count like `12.3 KiB` or `512 B`
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+
+    for unit in ["KiB", "MiB"] {
+        if size < 1024.0 {
+            return format!("{size:.1} {unit}");
+        }
+
+        size /= 1024.0;
+    }
+
+    format!("{size:.1} GiB")
+}
+
+/// formats a signed byte count like `+12.3 KiB` or `-512 B`
+pub fn format_size_delta(bytes: i64) -> String {
+    let sign = if bytes < 0 { "-" } else { "+" };
+
+    format!("{sign}{}", format_size(bytes.unsigned_abs()))
+}
+
 #[cfg(test)]
 mod test {
     #![allow(clippy::unwrap_used)]
     use cargo_metadata::cargo_platform::{Cfg, CfgExpr, Ident, Platform};
     use std::io::Write;
+    use std::time::Duration;
     use tempfile::NamedTempFile;
 
     use crate::{
-        io::util::{get_dependecy_item_from_doc, get_dependency_path, toml_document_from_path},
+        io::util::{
+            format_size, format_size_delta, get_dependecy_item_from_doc, get_dependency_path,
+            glob_matches, parse_duration, toml_document_from_path,
+        },
         project::dependency::DependencyType,
     };
 
@@ -227,4 +322,50 @@ clap = {{ version = "4.5.31", features = ["derive"] }}
             "target.x86_64.workspace.dependencies"
         );
     }
+
+    #[test]
+    fn parse_duration_works() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration(&format!("{}h", u64::MAX / 60)).is_err());
+        assert!(parse_duration(&format!("{}m", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn glob_matches_works() {
+        assert!(glob_matches("tokio", "tokio"));
+        assert!(!glob_matches("tokio", "tokio-util"));
+        assert!(glob_matches("tokio*", "tokio-util"));
+        assert!(glob_matches("*-util", "tokio-util"));
+        assert!(glob_matches("*o*o*", "tokio"));
+        assert!(glob_matches("serde?json", "serde_json"));
+        assert!(glob_matches("*", ""));
+
+        assert!(!glob_matches("?", ""));
+        assert!(!glob_matches("*-util", "tokio"));
+        assert!(!glob_matches("tok?", "tokio"));
+    }
+
+    #[test]
+    fn format_size_works() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn format_size_delta_works() {
+        assert_eq!(format_size_delta(0), "+0 B");
+        assert_eq!(format_size_delta(-512), "-512 B");
+        assert_eq!(format_size_delta(12_595), "+12.3 KiB");
+        assert_eq!(format_size_delta(-3 * 1024 * 1024), "-3.0 MiB");
+        assert_eq!(format_size_delta(5 * 1024 * 1024 * 1024), "+5.0 GiB");
+    }
 }
@@ -4,11 +4,25 @@ use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
 use color_eyre::Result;
 use console::Term;
+use std::ops::Not;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use crate::edit::display::Display;
-use crate::prune::prune;
+use crate::fix::fix;
+use crate::io::util::parse_duration;
+use crate::project::document::Document;
+use crate::prune::check::CheckStep;
+use crate::prune::filter::DependencyKind;
+use crate::prune::impact::impact;
+use crate::prune::known::{KnownFeatures, display_known_features};
+use crate::prune::matrix::FeatureMatrix;
+use crate::prune::report::ReportFormat;
+use crate::prune::{INTERRUPTED, prune};
 
 mod edit;
+mod fix;
 mod prune;
 
 mod project;
@@ -37,7 +51,22 @@ struct FeaturesArgs {
 
 #[derive(Subcommand)]
 enum FeaturesSubCommands {
-    Prune(PruneArgs),
+    Prune(Box<PruneArgs>),
+    /// measure how much each enabled feature adds to a clean build
+    Impact(ImpactArgs),
+    /// enable the features of dependencies rustc reports as missing until the project builds
+    Fix,
+    /// features which compile without problems but remove functionality
+    Known {
+        #[command(subcommand)]
+        sub: KnownSubCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum KnownSubCommands {
+    /// list the known features of every source prune uses
+    List,
 }
 
 #[derive(Args)]
@@ -55,6 +84,84 @@ pub struct PruneArgs {
     /// only check features that enable extra dependencies
     #[arg(long, short = 'd')]
     only_dependency: bool,
+    /// switch dependencies to `default-features = false` and only add back the default features which are needed - always uses the ddmin strategy
+    #[arg(long, conflicts_with_all = ["strategy", "only_dependency"])]
+    minimal_defaults: bool,
+    /// number of temporary project copies which are checked in parallel
+    #[arg(long, short, default_value_t = 1, conflicts_with = "no_tmp")]
+    jobs: usize,
+    /// how the features of a dependency are searched for removable ones
+    #[arg(long, short, default_value_t, value_enum)]
+    strategy: PruneStrategy,
+    /// replaces the steps used to check if a feature is removable [build, check, test, clippy, nextest, doc, exec:<command>]
+    #[arg(long = "step", value_name = "STEP")]
+    steps: Vec<CheckStep>,
+    /// continue an interrupted prune run by skipping already checked features
+    #[arg(long)]
+    resume: bool,
+    /// write a report of all checked features to <REPORT>
+    #[arg(long)]
+    report: Option<PathBuf>,
+    #[arg(long, default_value_t, value_enum, requires = "report")]
+    report_format: ReportFormat,
+    /// only mark features as removable if the checks succeed for every <TARGET> [default: host]
+    #[arg(long = "target", value_name = "TARGET")]
+    targets: Vec<String>,
+    /// the configurations of the project's own features the checks are run with [default: default]
+    #[arg(long, value_enum, value_name = "SET")]
+    feature_matrix: Vec<FeatureMatrix>,
+    /// a check step which takes longer than <TIMEOUT> is killed and the feature is kept e.g. 10m
+    #[arg(long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+    /// stop checking new features after <BUDGET> and keep the unchecked ones e.g. 2h - the removals are still verified
+    #[arg(long, value_parser = parse_duration)]
+    budget: Option<Duration>,
+    /// where the temporary copies keep their build output between runs [default: target/cargo-features-manager]
+    #[arg(long, value_name = "DIR", conflicts_with = "no_tmp")]
+    cache_dir: Option<PathBuf>,
+    /// check every dependency again - even if it did not change since the last run
+    #[arg(long)]
+    no_cache: bool,
+    /// also check if whole dependencies can be removed
+    #[arg(long)]
+    unused: bool,
+    /// only prune packages matching <GLOB>
+    #[arg(long = "package", short, value_name = "GLOB")]
+    packages: Vec<String>,
+    /// only prune dependencies matching <GLOB>
+    #[arg(long = "dependency", value_name = "GLOB")]
+    dependencies: Vec<String>,
+    /// do not prune dependencies matching <GLOB>
+    #[arg(long = "exclude-dependency", value_name = "GLOB")]
+    excluded_dependencies: Vec<String>,
+    /// do not check if the project still works with all removable features disabled at once
+    #[arg(long)]
+    no_verify: bool,
+    /// review the removable features in a list before they are disabled - unused dependencies can not be reviewed
+    #[arg(long, conflicts_with_all = ["dry_run", "unused"])]
+    review: bool,
+    /// exit with an error if any feature can be removed - the manifests are never changed
+    #[arg(long, conflicts_with = "review")]
+    check: bool,
+    /// only prune dependencies of the given <KIND>
+    #[arg(long = "kind", value_enum, value_name = "KIND")]
+    kinds: Vec<DependencyKind>,
+}
+
+#[derive(Args)]
+pub struct ImpactArgs {
+    /// do not copy the project into a temporary directory
+    #[arg(long, short = 't')]
+    no_tmp: bool,
+    /// only measure features that enable extra dependencies
+    #[arg(long, short = 'd')]
+    only_dependency: bool,
+    /// measure the size of the release binaries instead of the build time
+    #[arg(long)]
+    size: bool,
+    /// write the measurements as json to <REPORT>
+    #[arg(long)]
+    report: Option<PathBuf>,
 }
 
 #[derive(clap::ValueEnum, Clone, Default, Debug)]
@@ -65,6 +172,17 @@ enum CleanLevel {
     Dependency,
 }
 
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+enum PruneStrategy {
+    /// check every feature on its own
+    #[default]
+    Linear,
+    /// check all features at once and split them in half if the check fails
+    Bisect,
+    /// like bisect but keeps removable features disabled for the following checks
+    Ddmin,
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
 
@@ -87,6 +205,8 @@ fn main() -> Result<()> {
 
 fn run(args: FeaturesArgs) -> Result<()> {
     let _ = ctrlc::set_handler(|| {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+
         let term = Term::stdout();
         term.show_cursor().expect("could not enable cursor");
     });
@@ -94,7 +214,21 @@ fn run(args: FeaturesArgs) -> Result<()> {
     if let Some(sub) = args.sub {
         match sub {
             FeaturesSubCommands::Prune(args) => {
-                prune(&args)?;
+                if prune(&args)?.not() {
+                    std::process::exit(1);
+                }
+            }
+            FeaturesSubCommands::Impact(args) => {
+                impact(&args)?;
+            }
+            FeaturesSubCommands::Fix => {
+                fix()?;
+            }
+            FeaturesSubCommands::Known {
+                sub: KnownSubCommands::List,
+            } => {
+                let document = Document::new(".")?;
+                display_known_features(&KnownFeatures::load(document.root_path())?)?;
             }
         }
     } else {
@@ -14,6 +14,8 @@ pub struct Dependency {
     pub rename: Option<String>,
     pub comment: Option<String>,
     pub version: String,
+    /// the version from the Cargo.lock - none if the dependency is not used
+    pub resolved_version: Option<String>,
 
     pub workspace: bool,
     pub kind: DependencyType,
@@ -85,6 +87,45 @@ impl Dependency {
         true
     }
 
+    pub fn get_enabled_features(&self) -> Vec<String> {
+        self.features
+            .iter()
+            .filter(|(_, data)| data.is_enabled())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// returns the default features and all features enabled by them
+    pub fn get_default_features(&self) -> Vec<String> {
+        let mut default_features = self
+            .features
+            .iter()
+            .filter(|(_, data)| data.is_default)
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+
+        let mut index = 0;
+
+        while let Some(name) = default_features.get(index) {
+            let sub_features = self
+                .get_feature(name)
+                .map(|data| data.sub_features.clone())
+                .unwrap_or_default();
+
+            for sub_feature in sub_features {
+                if sub_feature.kind == SubFeatureType::Normal
+                    && !default_features.contains(&sub_feature.name)
+                {
+                    default_features.push(sub_feature.name);
+                }
+            }
+
+            index += 1;
+        }
+
+        default_features
+    }
+
     pub fn get_features_to_enable(&self) -> Vec<String> {
         let can_use_default = self.can_use_default();
 
@@ -225,3 +266,57 @@ impl From<DependencyKind> for DependencyType {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::dependency::feature::{
+        EnabledState, FeatureData, SubFeature, SubFeatureType,
+    };
+    use crate::project::dependency::{Dependency, DependencyType};
+    use itertools::Itertools;
+    use std::collections::HashMap;
+
+    fn feature(sub_features: &[&str], is_default: bool) -> FeatureData {
+        FeatureData {
+            sub_features: sub_features
+                .iter()
+                .map(|name| SubFeature {
+                    name: name.to_string(),
+                    kind: SubFeatureType::Normal,
+                })
+                .collect(),
+            is_default,
+            enabled_state: EnabledState::Normal(true),
+        }
+    }
+
+    #[test]
+    fn default_features_include_their_sub_features() {
+        let dependency = Dependency {
+            name: "dep".to_string(),
+            rename: None,
+            comment: None,
+            version: "1".to_string(),
+            resolved_version: None,
+            workspace: false,
+            kind: DependencyType::Normal,
+            target: None,
+            features: HashMap::from([
+                ("default".to_string(), feature(&["std"], false)),
+                ("std".to_string(), feature(&["alloc"], true)),
+                ("alloc".to_string(), feature(&[], false)),
+                ("derive".to_string(), feature(&[], false)),
+            ]),
+        };
+
+        assert_eq!(
+            dependency
+                .get_default_features()
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            ["alloc", "std"]
+        );
+    }
+}
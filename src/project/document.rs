@@ -143,6 +143,36 @@ impl Document {
             .map(|workspace_index| self.get_package_by_index(workspace_index))
     }
 
+    /// the name without emojis which depend on the terminal - `[workspace]` for `[workspace.dependencies]`
+    pub fn plain_package_name(&self, package_name: &str) -> Result<String> {
+        let package = self.get_package(package_name)?;
+
+        let is_workspace_package = self
+            .workspace_index
+            .is_some_and(|index| self.packages[index].name == package.name);
+
+        if is_workspace_package {
+            Ok("[workspace]".to_string())
+        } else {
+            Ok(package.name.clone())
+        }
+    }
+
+    /// e.g. `app/serde` - the crate name is used as the name of a dependency
+    pub fn plain_dependency_path(
+        &self,
+        package_name: &str,
+        dependency_name: &str,
+    ) -> Result<String> {
+        let dependency = self.get_package(package_name)?.get_dep(dependency_name)?;
+
+        Ok(format!(
+            "{}/{}",
+            self.plain_package_name(package_name)?,
+            dependency.name
+        ))
+    }
+
     pub fn is_workspace(&self) -> bool {
         self.packages.len() > 1
     }
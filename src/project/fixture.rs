@@ -0,0 +1,102 @@
+use std::fs;
+use tempfile::TempDir;
+
+/// a project made of the given files in a temporary directory
+///
+/// only use path dependencies so `cargo metadata` works offline
+pub fn project(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().expect("could not create a temporary directory");
+
+    for (path, content) in files {
+        let path = dir.path().join(path);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("could not create the directory");
+        }
+
+        fs::write(path, content).expect("could not write the file");
+    }
+
+    dir
+}
+
+/// a package `app` with a path dependency `dep` which has the features `a`, `b` and `default = ["a"]`
+pub fn app_with_dep(dependencies: &str) -> TempDir {
+    project(&[
+        (
+            "app/Cargo.toml",
+            &format!(
+                r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2024"
+
+{dependencies}
+"#
+            ),
+        ),
+        ("app/src/lib.rs", ""),
+        (
+            "dep/Cargo.toml",
+            r#"[package]
+name = "dep"
+version = "0.1.0"
+edition = "2024"
+
+[features]
+default = ["a"]
+a = []
+b = []
+"#,
+        ),
+        ("dep/src/lib.rs", ""),
+    ])
+}
+
+/// a workspace with the member `app` which inherits the path dependency `dep` from `[workspace.dependencies]`
+///
+/// `dep` has the features `a`, `b` and `default = ["a"]`
+pub fn workspace_with_dep(workspace_dependency: &str, member_dependency: &str) -> TempDir {
+    project(&[
+        (
+            "Cargo.toml",
+            &format!(
+                r#"[workspace]
+members = ["app"]
+resolver = "3"
+
+[workspace.dependencies]
+dep = {workspace_dependency}
+"#
+            ),
+        ),
+        (
+            "app/Cargo.toml",
+            &format!(
+                r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2024"
+
+[dependencies]
+dep = {member_dependency}
+"#
+            ),
+        ),
+        ("app/src/lib.rs", ""),
+        (
+            "dep/Cargo.toml",
+            r#"[package]
+name = "dep"
+version = "0.1.0"
+edition = "2024"
+
+[features]
+default = ["a"]
+a = []
+b = []
+"#,
+        ),
+        ("dep/src/lib.rs", ""),
+    ])
+}
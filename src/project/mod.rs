@@ -1,3 +1,6 @@
 pub mod dependency;
 pub mod document;
 pub mod package;
+
+#[cfg(test)]
+pub mod fixture;
@@ -4,6 +4,8 @@ use color_eyre::eyre::{bail, eyre};
 pub struct Package {
     pub dependencies: Vec<Dependency>,
     pub name: String,
+    /// the features the package itself defines - excluding `default`
+    pub features: Vec<String>,
     // path include the Cargo.toml
     pub manifest_path: String,
 }
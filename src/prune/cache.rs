@@ -0,0 +1,312 @@
+use crate::io::fingerprint::{files_in, fingerprint, fingerprint_text};
+use crate::io::util::toml_document_from_path;
+use crate::project::document::Document;
+use crate::prune::checkpoint::Verdict;
+use crate::prune::report::DependencyId;
+use crate::prune::scheduler::Job;
+use crate::prune::{DependencyName, FeatureName, PackageName, TMP_DIR_PREFIX};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Not;
+use std::path::{Path, PathBuf};
+
+/// the verdicts of previous runs which can be reused as long as nothing they depend on changed
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: CacheKey,
+    verdicts: Vec<Verdict>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct CacheKey {
+    /// the names do not depend on the locale
+    package: PackageName,
+    dependency: DependencyId,
+    resolved_version: Option<String>,
+    features: Vec<FeatureName>,
+    /// hash of the sources and the dependencies of the workspace and the Cargo.lock
+    sources: String,
+    /// the steps, targets and feature sets the verdicts were computed with
+    checks: String,
+}
+
+impl CacheKey {
+    pub fn new(
+        document: &Document,
+        package_name: &PackageName,
+        dependency_name: &DependencyName,
+        features: &[FeatureName],
+        sources: String,
+        checks: String,
+    ) -> Result<Self> {
+        let dependency = document
+            .get_package(package_name)?
+            .get_dep(dependency_name)?;
+        let (package, dependency_id) = DependencyId::new(document, package_name, dependency_name)?;
+
+        Ok(Self {
+            package,
+            dependency: dependency_id,
+            resolved_version: dependency.resolved_version.clone(),
+            features: features.iter().sorted().cloned().collect(),
+            sources,
+            checks,
+        })
+    }
+
+    fn is_same_dependency(&self, other: &CacheKey) -> bool {
+        self.package == other.package && self.dependency == other.dependency
+    }
+}
+
+impl Cache {
+    pub fn path(document: &Document) -> PathBuf {
+        document
+            .root_path()
+            .join("target")
+            .join("cargo-features-manager")
+            .join("prune-cache.json")
+    }
+
+    /// an unreadable cache is treated as empty
+    pub fn load<P: AsRef<Path>>(path: P) -> Cache {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// hash of all files of the workspace - only the dependencies and features of the manifests are part of it
+    ///
+    /// every package can break the checks of another one e.g. through `[workspace.dependencies]` or a shared path
+    /// dependency, so once the removals of a run are applied every dependency of the workspace is checked again
+    pub fn sources(document: &Document) -> Result<String> {
+        let root_path = document.root_path();
+
+        let manifest_paths = document
+            .get_packages()
+            .iter()
+            .map(|package| PathBuf::from(&package.manifest_path))
+            .chain([root_path.join("Cargo.toml")])
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let mut paths = files_in(root_path, &|name| {
+            name == "target" || name == ".git" || name.starts_with(TMP_DIR_PREFIX)
+        })?;
+
+        paths.retain(|path| manifest_paths.contains(path).not());
+        paths.push(root_path.join("Cargo.lock"));
+
+        let mut manifests = String::new();
+
+        for manifest_path in &manifest_paths {
+            manifests.push_str(&format!(
+                "{}\n{}",
+                manifest_path.display(),
+                manifest_dependencies(manifest_path)?
+            ));
+        }
+
+        Ok(format!(
+            "{}-{}",
+            fingerprint(&paths)?,
+            fingerprint_text(&manifests)
+        ))
+    }
+
+    pub fn verdicts(&self, key: &CacheKey) -> Option<&Vec<Verdict>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == *key)
+            .map(|entry| &entry.verdicts)
+    }
+
+    /// replaces the verdicts of the dependency
+    pub fn insert(&mut self, key: CacheKey, job: &Job) {
+        self.entries
+            .retain(|entry| entry.key.is_same_dependency(&key).not());
+
+        self.entries.push(CacheEntry {
+            key,
+            verdicts: Verdict::of_job(job),
+        });
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+/// the dependency tables, features and workspace members of the manifest - the rest e.g. the version of the package is
+/// not used
+fn manifest_dependencies(manifest_path: &Path) -> Result<String> {
+    let document = toml_document_from_path(manifest_path)?;
+
+    let mut text = String::new();
+
+    for key in [
+        "features",
+        "dependencies",
+        "dev-dependencies",
+        "build-dependencies",
+        "target",
+        "patch",
+        "workspace",
+    ] {
+        let items = match (key, document.get(key)) {
+            ("workspace", Some(workspace)) => ["members", "exclude", "dependencies"]
+                .into_iter()
+                .filter_map(|key| workspace.get(key))
+                .collect_vec(),
+            (_, item) => item.into_iter().collect_vec(),
+        };
+
+        for item in items {
+            text.push_str(&format!("{key}\n{item}\n"));
+        }
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::document::Document;
+    use crate::project::fixture::{app_with_dep, workspace_with_dep};
+    use crate::prune::cache::{Cache, CacheEntry, CacheKey};
+    use std::fs;
+
+    const MANIFEST: &str = r#"[dev-dependencies]
+dep = { path = "../dep", features = ["b"] }"#;
+
+    fn key(document: &Document, sources: &str) -> CacheKey {
+        let dependency_name = document.get_package("app").unwrap().get_deps()[0].get_name();
+
+        CacheKey::new(
+            document,
+            &"app".to_string(),
+            &dependency_name,
+            &["b".to_string(), "a".to_string()],
+            sources.to_string(),
+            "check".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn key_uses_plain_names() {
+        let dir = app_with_dep(MANIFEST);
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let json = serde_json::to_value(key(&document, "sources")).unwrap();
+
+        assert_eq!(json["package"], "app");
+        assert_eq!(json["dependency"]["name"], "dep");
+        assert_eq!(json["dependency"]["kind"], "dev");
+        assert_eq!(json["features"], serde_json::json!(["a", "b"]));
+    }
+
+    #[test]
+    fn sources_change_with_the_package() {
+        let dir = app_with_dep(MANIFEST);
+        let app = dir.path().join("app");
+        let manifest_path = app.join("Cargo.toml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap();
+
+        let sources = || {
+            let document = Document::new(&app).unwrap();
+            Cache::sources(&document).unwrap()
+        };
+
+        let original = sources();
+
+        fs::write(
+            &manifest_path,
+            manifest.replace("edition", "description = \"app\"\nedition"),
+        )
+        .unwrap();
+        assert_eq!(
+            sources(),
+            original,
+            "the description of the package is not used"
+        );
+
+        fs::write(
+            &manifest_path,
+            manifest.replace("[\"b\"]", "[\"a\", \"b\"]"),
+        )
+        .unwrap();
+        let with_features = sources();
+        assert_ne!(with_features, original);
+
+        fs::write(app.join("src/lib.rs"), "pub fn f() {}").unwrap();
+        assert_ne!(sources(), with_features);
+    }
+
+    #[test]
+    fn sources_change_with_the_workspace() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", features = ["b"] }"#,
+            "{ workspace = true }",
+        );
+        let manifest_path = dir.path().join("Cargo.toml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap();
+
+        let sources = || {
+            let document = Document::new(dir.path()).unwrap();
+            Cache::sources(&document).unwrap()
+        };
+
+        let original = sources();
+
+        // only the root manifest changes - not the one of `app`
+        fs::write(
+            &manifest_path,
+            manifest.replace("[\"b\"]", "[\"a\", \"b\"]"),
+        )
+        .unwrap();
+        let with_features = sources();
+        assert_ne!(with_features, original);
+
+        // a path dependency outside of `app`
+        fs::write(dir.path().join("dep/src/lib.rs"), "pub fn f() {}").unwrap();
+        let with_dep_sources = sources();
+        assert_ne!(with_dep_sources, with_features);
+
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("target/debug/app"), "binary").unwrap();
+        assert_eq!(sources(), with_dep_sources, "the build output is not used");
+    }
+
+    #[test]
+    fn verdicts_need_the_same_key() {
+        let dir = app_with_dep(MANIFEST);
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let cache = Cache {
+            entries: vec![CacheEntry {
+                key: key(&document, "old"),
+                verdicts: vec![],
+            }],
+        };
+
+        assert!(cache.verdicts(&key(&document, "old")).is_some());
+        assert!(cache.verdicts(&key(&document, "new")).is_none());
+    }
+}
@@ -0,0 +1,549 @@
+use crate::PruneArgs;
+use crate::io::util::{get_item_from_doc, toml_document_from_path};
+use crate::project::document::Document;
+use crate::prune::INTERRUPTED;
+use crate::prune::matrix::{FeatureMatrix, FeatureSet, feature_sets};
+use crate::prune::target::Target;
+use cargo_metadata::Message;
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel};
+use clap::ValueEnum;
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, bail, eyre};
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const CONFIG_PATHS: [&str; 2] = [
+    "workspace.metadata.cargo-features-manager",
+    "package.metadata.cargo-features-manager",
+];
+
+/// a single command which has to succeed for a feature to be removable
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckStep {
+    /// `cargo build` for every kind of target of every workspace member
+    Build,
+    /// `cargo check` for every kind of target of every workspace member
+    Check,
+    Test,
+    Clippy,
+    Nextest,
+    Doc,
+    /// an arbitrary shell command e.g. `exec:just verify`
+    Exec(String),
+}
+
+impl CheckStep {
+    pub fn is_test(&self) -> bool {
+        matches!(self, CheckStep::Test | CheckStep::Nextest)
+    }
+
+    fn run<P: AsRef<Path>>(&self, path: P, config: &StepConfig) -> Result<CheckOutcome> {
+        let target = config.target;
+
+        const TARGETS: [&str; 6] = [
+            "--bins",
+            "--lib",
+            "--examples",
+            "--tests",
+            "--benches",
+            "--all-targets",
+        ];
+
+        match self {
+            CheckStep::Build | CheckStep::Check => {
+                for build_target in TARGETS {
+                    let outcome = run_cargo(
+                        &path,
+                        &[&self.to_string(), "--workspace", build_target],
+                        config,
+                    )?;
+
+                    if !outcome.is_success() {
+                        return Ok(outcome);
+                    }
+                }
+
+                Ok(CheckOutcome::Success)
+            }
+            // tests of foreign targets can only be built
+            CheckStep::Test if !target.is_host => {
+                run_cargo(path, &["test", "--workspace", "--no-run"], config)
+            }
+            CheckStep::Test => run_cargo(path, &["test", "--workspace"], config),
+            CheckStep::Clippy => {
+                run_cargo(path, &["clippy", "--workspace", "--all-targets"], config)
+            }
+            CheckStep::Nextest => {
+                let subcommand = if target.is_host { "run" } else { "list" };
+                let mut args = vec!["nextest", subcommand, "--workspace"];
+
+                if target.is_explicit {
+                    args.extend(["--target", &target.triple]);
+                }
+
+                let feature_args = config.feature_set.cargo_args();
+                args.extend(feature_args.iter().map(String::as_str));
+
+                // nextest does not support cargo's json messages
+                run(
+                    Command::new("cargo").current_dir(path).args(&args),
+                    &format!("cargo {}", args.join(" ")),
+                    config,
+                )
+            }
+            CheckStep::Doc => run_cargo(path, &["doc", "--workspace", "--no-deps"], config),
+            CheckStep::Exec(command) => {
+                let mut shell = if cfg!(windows) {
+                    let mut shell = Command::new("cmd");
+                    shell.arg("/C");
+                    shell
+                } else {
+                    let mut shell = Command::new("sh");
+                    shell.arg("-c");
+                    shell
+                };
+
+                if target.is_explicit {
+                    shell.env("CARGO_BUILD_TARGET", &target.triple);
+                }
+
+                run(shell.arg(command).current_dir(path), command, config)
+            }
+        }
+    }
+}
+
+impl FromStr for CheckStep {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(command) = s.strip_prefix("exec:") {
+            return Ok(CheckStep::Exec(command.trim().to_string()));
+        }
+
+        match s {
+            "build" => Ok(CheckStep::Build),
+            "check" => Ok(CheckStep::Check),
+            "test" => Ok(CheckStep::Test),
+            "clippy" => Ok(CheckStep::Clippy),
+            "nextest" => Ok(CheckStep::Nextest),
+            "doc" => Ok(CheckStep::Doc),
+            _ => Err(format!(
+                "unknown step \"{s}\" - expected one of build, check, test, clippy, nextest, doc or exec:<command>"
+            )),
+        }
+    }
+}
+
+impl Display for CheckStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStep::Build => f.write_str("build"),
+            CheckStep::Check => f.write_str("check"),
+            CheckStep::Test => f.write_str("test"),
+            CheckStep::Clippy => f.write_str("clippy"),
+            CheckStep::Nextest => f.write_str("nextest"),
+            CheckStep::Doc => f.write_str("doc"),
+            CheckStep::Exec(command) => write!(f, "exec:{command}"),
+        }
+    }
+}
+
+/// everything a single run of a step depends on
+struct StepConfig<'a> {
+    target: &'a Target,
+    feature_set: &'a FeatureSet,
+    /// the step is killed and counts as failed once it takes longer
+    timeout: Option<Duration>,
+    /// used as `CARGO_TARGET_DIR`
+    target_dir: Option<&'a Path>,
+}
+
+pub enum CheckOutcome {
+    Success,
+    /// why the check failed e.g. `error[E0433] in src/main.rs:12`
+    Failure(String),
+}
+
+impl CheckOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, CheckOutcome::Success)
+    }
+
+    pub fn reason(&self) -> Option<&String> {
+        match self {
+            CheckOutcome::Success => None,
+            CheckOutcome::Failure(reason) => Some(reason),
+        }
+    }
+}
+
+/// runs all configured steps to decide if the project still works
+pub struct Checker {
+    steps: Vec<CheckStep>,
+    /// a feature is only removable if all steps succeed for every target and feature set
+    targets: Vec<Target>,
+    feature_sets: Vec<FeatureSet>,
+    timeout: Option<Duration>,
+}
+
+impl Checker {
+    /// the steps and feature matrix given as arguments take precedence over the ones configured in the Cargo.toml
+    pub fn new(args: &PruneArgs, document: &Document) -> Result<Self> {
+        let root_path = document.root_path();
+
+        let steps = if args.steps.is_empty() {
+            get_configured(root_path, "prune-steps", |step| step.parse())?
+                .unwrap_or(vec![CheckStep::Build, CheckStep::Test])
+        } else {
+            args.steps.clone()
+        };
+
+        let steps = select_steps(steps, args.skip_tests)?;
+
+        let targets = if args.targets.is_empty() {
+            vec![Target::host()?]
+        } else {
+            args.targets
+                .iter()
+                .map(|triple| Target::new(triple))
+                .collect::<Result<Vec<Target>>>()?
+        };
+
+        let feature_matrix = if args.feature_matrix.is_empty() {
+            get_configured(root_path, "prune-feature-matrix", |entry| {
+                FeatureMatrix::from_str(entry, false)
+            })?
+            .unwrap_or(vec![FeatureMatrix::Default])
+        } else {
+            args.feature_matrix.clone()
+        };
+
+        Ok(Self {
+            steps,
+            targets,
+            feature_sets: feature_sets(&feature_matrix, document.get_packages())?,
+            timeout: args.timeout,
+        })
+    }
+
+    /// everything the verdicts depend on besides the project itself
+    pub fn description(&self) -> String {
+        format!(
+            "steps: {} targets: {} feature sets: {} timeout: {:?}",
+            self.steps.iter().join(","),
+            self.targets.iter().map(|target| &target.triple).join(","),
+            self.feature_sets.iter().join(","),
+            self.timeout
+        )
+    }
+
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    pub fn check<P: AsRef<Path>>(
+        &self,
+        path: P,
+        target_dir: Option<&Path>,
+    ) -> Result<CheckOutcome> {
+        for target in &self.targets {
+            for (index, feature_set) in self.feature_sets.iter().enumerate() {
+                for step in &self.steps {
+                    // commands do not know about the feature sets so there is no need to repeat them
+                    if index > 0 && matches!(step, CheckStep::Exec(_)) {
+                        continue;
+                    }
+
+                    let config = StepConfig {
+                        target,
+                        feature_set,
+                        timeout: self.timeout,
+                        target_dir,
+                    };
+
+                    let outcome = step.run(&path, &config)?;
+
+                    if let CheckOutcome::Failure(reason) = outcome {
+                        return Ok(CheckOutcome::Failure(describe_failure(
+                            reason,
+                            target,
+                            feature_set,
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(CheckOutcome::Success)
+    }
+}
+
+/// removes the test steps with `--skip-tests`
+///
+/// a check without any step always succeeds - every feature would be removable
+fn select_steps(mut steps: Vec<CheckStep>, skip_tests: bool) -> Result<Vec<CheckStep>> {
+    if steps.is_empty() {
+        bail!("no check steps are configured - prune needs at least one step");
+    }
+
+    if skip_tests {
+        steps.retain(|step| !step.is_test());
+
+        if steps.is_empty() {
+            bail!(
+                "--skip-tests removes every check step - add a step which is not a test e.g. `--step build`"
+            );
+        }
+    }
+
+    Ok(steps)
+}
+
+/// adds the target and feature set the failure occurred with if they are not obvious
+fn describe_failure(reason: String, target: &Target, feature_set: &FeatureSet) -> String {
+    let mut context = vec![];
+
+    if target.is_explicit && !reason.contains(&target.triple) {
+        context.push(target.triple.clone());
+    }
+
+    if *feature_set != FeatureSet::Default {
+        context.push(feature_set.to_string());
+    }
+
+    if context.is_empty() {
+        return reason;
+    }
+
+    format!("{reason} ({})", context.join(", "))
+}
+
+/// reads a list from the `cargo-features-manager` metadata of the root Cargo.toml
+fn get_configured<P, T, F>(root_path: P, key: &str, parse: F) -> Result<Option<Vec<T>>>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> std::result::Result<T, String>,
+{
+    let Ok(document) = toml_document_from_path(root_path.as_ref().join("Cargo.toml")) else {
+        return Ok(None);
+    };
+
+    for path in CONFIG_PATHS {
+        let path = format!("{path}.{key}");
+
+        let Ok(item) = get_item_from_doc(&path, &document) else {
+            continue;
+        };
+
+        let entries = item
+            .as_array()
+            .context(format!("could not parse {path} - expected an array"))?
+            .iter()
+            .map(|entry| {
+                parse(
+                    entry
+                        .as_str()
+                        .context(format!("could not parse {path} - expected strings"))?,
+                )
+                .map_err(|err| eyre!(err))
+            })
+            .collect::<Result<Vec<T>>>()?;
+
+        return Ok(Some(entries));
+    }
+
+    Ok(None)
+}
+
+pub fn clean<P: AsRef<Path>>(path: P, target_dir: Option<&Path>) -> Result<()> {
+    let mut command = Command::new("cargo");
+
+    if let Some(target_dir) = target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+
+    let mut child = command
+        .current_dir(path)
+        .arg("clean")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let _ = child.wait()?.code().ok_or(eyre!("Could not clear"))?;
+
+    Ok(())
+}
+
+fn run_cargo<P: AsRef<Path>>(path: P, args: &[&str], config: &StepConfig) -> Result<CheckOutcome> {
+    let mut args = args.to_vec();
+
+    if config.target.is_explicit {
+        args.extend(["--target", &config.target.triple]);
+    }
+
+    let feature_args = config.feature_set.cargo_args();
+    args.extend(feature_args.iter().map(String::as_str));
+
+    run(
+        Command::new("cargo")
+            .current_dir(path)
+            .args(&args)
+            .arg("--message-format=json"),
+        &format!("cargo {}", args.join(" ")),
+        config,
+    )
+}
+
+/// `description` is used as the reason if the command fails without a compiler error
+fn run(command: &mut Command, description: &str, config: &StepConfig) -> Result<CheckOutcome> {
+    let timeout = config.timeout;
+
+    if let Some(target_dir) = config.target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+
+    // a separate process group allows killing the whole process tree on a timeout
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("could not capture the output")?;
+
+    // the output is read on its own thread so the timeout can be checked in the meantime
+    let reader = thread::spawn(move || first_error(stdout));
+
+    let start = Instant::now();
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            kill_process_tree(&mut child)?;
+            bail!("interrupted");
+        }
+
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            kill_process_tree(&mut child)?;
+            break None;
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let first_error = reader
+        .join()
+        .map_err(|_| eyre!("could not read the output of {:?}", command.get_program()))??;
+
+    let Some(status) = status else {
+        return Ok(CheckOutcome::Failure(format!(
+            "timeout after {:?}: `{description}`",
+            timeout.unwrap_or_default()
+        )));
+    };
+
+    let code = status
+        .code()
+        .ok_or(eyre!("Could not run {:?}", command.get_program()))?;
+
+    if code == 0 {
+        return Ok(CheckOutcome::Success);
+    }
+
+    Ok(CheckOutcome::Failure(
+        first_error.unwrap_or(format!("`{description}` failed")),
+    ))
+}
+
+fn first_error(stdout: ChildStdout) -> Result<Option<String>> {
+    let mut first_error = None;
+
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        if let Message::CompilerMessage(message) = message?
+            && first_error.is_none()
+            && matches!(
+                message.message.level,
+                DiagnosticLevel::Error | DiagnosticLevel::Ice
+            )
+        {
+            first_error = Some(describe_error(&message.message));
+        }
+    }
+
+    Ok(first_error)
+}
+
+fn kill_process_tree(child: &mut Child) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let process_group = i32::try_from(child.id())?;
+
+        // SAFETY: kill only sends a signal - the negative id targets the process group of the child
+        unsafe {
+            libc::kill(-process_group, libc::SIGKILL);
+        }
+    }
+
+    #[cfg(windows)]
+    Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    let _ = child.kill();
+    child.wait()?;
+
+    Ok(())
+}
+
+/// e.g. `error[E0433] in src/main.rs:12`
+fn describe_error(diagnostic: &Diagnostic) -> String {
+    let error = match &diagnostic.code {
+        Some(code) => format!("error[{}]", code.code),
+        None => format!("error: {}", diagnostic.message.lines().join(" ")),
+    };
+
+    match diagnostic.spans.iter().find(|span| span.is_primary) {
+        Some(span) => format!("{error} in {}:{}", span.file_name, span.line_start),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::prune::check::{CheckStep, select_steps};
+
+    #[test]
+    fn skipping_tests_keeps_the_other_steps() {
+        let steps = select_steps(vec![CheckStep::Build, CheckStep::Test], true).unwrap();
+
+        assert_eq!(steps, [CheckStep::Build]);
+    }
+
+    #[test]
+    fn empty_steps_are_rejected() {
+        assert!(select_steps(vec![], false).is_err());
+        assert!(select_steps(vec![CheckStep::Test], true).is_err());
+        assert!(select_steps(vec![CheckStep::Nextest, CheckStep::Test], true).is_err());
+        assert!(select_steps(vec![CheckStep::Test], false).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::io::fingerprint::{fingerprint, fingerprint_text};
+use crate::project::document::Document;
+use crate::prune::scheduler::Job;
+use crate::prune::{DependencyName, FeatureName, PackageName};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the decisions of a prune run which are needed to resume it after an interruption
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// hash of all manifests and the Cargo.lock the verdicts were computed for
+    fingerprint: String,
+    verdicts: Vec<Verdict>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Verdict {
+    pub package: PackageName,
+    pub dependency: DependencyName,
+    pub feature: FeatureName,
+    pub is_removable: bool,
+    /// why a kept feature is required
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl Verdict {
+    /// the features of the job which are already known to be removable or required
+    pub fn of_job(job: &Job) -> Vec<Verdict> {
+        let removable = job.removable_features();
+        let kept = job.kept_features();
+
+        [(&removable, true), (&kept, false)]
+            .into_iter()
+            .flat_map(|(features, is_removable)| {
+                features.iter().map(move |feature| Verdict {
+                    package: job.package_name.clone(),
+                    dependency: job.dependency_name.clone(),
+                    feature: feature.clone(),
+                    is_removable,
+                    reason: job.failure_reason(feature).cloned(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_for(&self, package_name: &str, dependency_name: &str) -> bool {
+        self.package == package_name && self.dependency == dependency_name
+    }
+}
+
+impl Checkpoint {
+    pub fn path(document: &Document) -> PathBuf {
+        document
+            .root_path()
+            .join("target")
+            .join("cargo-features-manager")
+            .join("prune-checkpoint.json")
+    }
+
+    /// the verdicts depend on the manifests, the lock file and the `checks` they were found with
+    pub fn fingerprint(document: &Document, checks: &str) -> Result<String> {
+        let mut paths = document
+            .get_packages()
+            .iter()
+            .map(|package| PathBuf::from(&package.manifest_path))
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        paths.push(document.root_path().join("Cargo.lock"));
+
+        Ok(format!(
+            "{}-{}",
+            fingerprint(&paths)?,
+            fingerprint_text(checks)
+        ))
+    }
+
+    /// returns the verdicts of the last run if the project did not change since
+    pub fn load<P: AsRef<Path>>(path: P, fingerprint: &str) -> Result<Option<Vec<Verdict>>> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Ok(None);
+        };
+
+        let checkpoint: Checkpoint = serde_json::from_str(&content)?;
+
+        if checkpoint.fingerprint != fingerprint {
+            return Ok(None);
+        }
+
+        Ok(Some(checkpoint.verdicts))
+    }
+
+    pub fn save<P: AsRef<Path>>(path: P, fingerprint: &str, jobs: &[Job]) -> Result<()> {
+        let checkpoint = Checkpoint {
+            fingerprint: fingerprint.to_string(),
+            verdicts: jobs.iter().flat_map(Verdict::of_job).collect(),
+        };
+
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&checkpoint)?)?;
+
+        Ok(())
+    }
+
+    pub fn remove<P: AsRef<Path>>(path: P) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::PruneStrategy;
+    use crate::project::document::Document;
+    use crate::project::fixture::app_with_dep;
+    use crate::prune::checkpoint::{Checkpoint, Verdict};
+    use crate::prune::scheduler::Job;
+    use std::fs;
+
+    const CHECKS: &str =
+        "steps: build,test targets: x86_64-unknown-linux-gnu feature sets:  timeout: None";
+
+    #[test]
+    fn a_changed_fingerprint_drops_the_verdicts() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+        let app = dir.path().join("app");
+        let path = dir.path().join("checkpoint.json");
+
+        let fingerprint = Checkpoint::fingerprint(&Document::new(&app).unwrap(), CHECKS).unwrap();
+
+        let verdict = Verdict {
+            package: "app".to_string(),
+            dependency: "dep".to_string(),
+            feature: "b".to_string(),
+            is_removable: true,
+            reason: None,
+        };
+        let job = Job::new(
+            "app".to_string(),
+            "dep".to_string(),
+            vec!["b".to_string()],
+            vec![],
+            PruneStrategy::Linear,
+            &[verdict],
+        );
+
+        Checkpoint::save(&path, &fingerprint, &[job]).unwrap();
+
+        let verdicts = Checkpoint::load(&path, &fingerprint).unwrap().unwrap();
+        assert_eq!(verdicts.len(), 1);
+        assert!(verdicts[0].is_for("app", "dep") && verdicts[0].is_removable);
+
+        // e.g. the removals of the interrupted run were applied by hand
+        let manifest_path = app.join("Cargo.toml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap();
+        fs::write(&manifest_path, manifest.replace("[\"b\"]", "[]")).unwrap();
+
+        let changed = Checkpoint::fingerprint(&Document::new(&app).unwrap(), CHECKS).unwrap();
+
+        assert_ne!(changed, fingerprint);
+        assert!(Checkpoint::load(&path, &changed).unwrap().is_none());
+    }
+
+    #[test]
+    fn changed_checks_drop_the_verdicts() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path().join("app")).unwrap();
+        let path = dir.path().join("checkpoint.json");
+
+        let fingerprint = Checkpoint::fingerprint(&document, CHECKS).unwrap();
+
+        let job = Job::new(
+            "app".to_string(),
+            "dep".to_string(),
+            vec!["b".to_string()],
+            vec![],
+            PruneStrategy::Linear,
+            &[],
+        );
+
+        Checkpoint::save(&path, &fingerprint, &[job]).unwrap();
+
+        // a feature which is only removable for `cargo build` may be required by the tests
+        let changed = Checkpoint::fingerprint(
+            &document,
+            &CHECKS.replace("steps: build,test", "steps: build"),
+        )
+        .unwrap();
+
+        assert_ne!(changed, fingerprint);
+        assert!(Checkpoint::load(&path, &changed).unwrap().is_none());
+        assert!(Checkpoint::load(&path, &fingerprint).unwrap().is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::project::document::Document;
-use crate::prune::{DependencyName, FeatureName, FeaturesMap};
+use crate::prune::scheduler::Job;
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName};
 use color_eyre::Result;
 use console::{Term, style};
 use itertools::Itertools;
@@ -9,6 +10,13 @@ use std::ops::Not;
 
 type IsKnownFeature = bool;
 
+struct RunningFeature {
+    dependency_name: DependencyName,
+    dependency_feature_count: usize,
+    checked_features_count: usize,
+    feature_names: Vec<FeatureName>,
+}
+
 pub struct Display {
     term: Term,
 
@@ -21,22 +29,48 @@ pub struct Display {
 
     package_name: String,
     package_feature_count: usize,
-    package_checked_features_count: usize,
+    package_checked_features_count: HashMap<PackageName, usize>,
 
-    dependency_name: String,
-    dependency_feature_count: usize,
+    /// the feature each worker is currently checking
+    running_features: Vec<Option<RunningFeature>>,
 
     is_terminal: bool,
+    /// every dependency is printed with its plain path instead of below its package
+    is_plain: bool,
+    /// e.g. `app/serde` for plain output
+    plain_paths: HashMap<(PackageName, DependencyName), String>,
 }
 
 impl Display {
-    pub fn new(features_to_test: &FeaturesMap, document: &Document) -> Self {
+    /// a plain display only prints the results - e.g. for CI
+    pub fn new(
+        features_to_test: &FeaturesMap,
+        document: &Document,
+        worker_count: usize,
+        is_plain: bool,
+    ) -> Self {
         let feature_count = features_to_test
             .values()
             .flat_map(|dependencies| dependencies.values())
             .flatten()
             .count();
 
+        let plain_paths = features_to_test
+            .iter()
+            .flat_map(|(package_name, dependencies)| {
+                dependencies
+                    .keys()
+                    .map(move |dependency_name| (package_name, dependency_name))
+            })
+            .filter_map(|(package_name, dependency_name)| {
+                let path = document
+                    .plain_dependency_path(package_name, dependency_name)
+                    .ok()?;
+
+                Some(((package_name.clone(), dependency_name.clone()), path))
+            })
+            .collect();
+
         let package_inset = if features_to_test.len() == 1 { 0 } else { 2 };
         let dependency_inset = if features_to_test.len() == 1 { 2 } else { 4 };
 
@@ -47,12 +81,13 @@ impl Display {
             is_workspace: document.is_workspace(),
             package_name: "?".to_string(),
             package_feature_count: 0,
-            package_checked_features_count: 0,
-            dependency_name: "?".to_string(),
+            package_checked_features_count: HashMap::new(),
+            running_features: (0..worker_count).map(|_| None).collect(),
             term: Term::stdout(),
             checked_features_count: 0,
-            dependency_feature_count: 0,
-            is_terminal: std::io::stdout().is_terminal(),
+            is_terminal: is_plain.not() && std::io::stdout().is_terminal(),
+            is_plain,
+            plain_paths,
         }
     }
 
@@ -66,20 +101,53 @@ impl Display {
 
     pub fn finish(&self) -> Result<()> {
         if self.is_terminal {
+            self.term.clear_to_end_of_screen()?;
             self.term.show_cursor()?;
         }
         Ok(())
     }
 
-    pub fn display_known_features_notice(&mut self) -> Result<()> {
+    /// `reasons` explain the known features which define why they are kept
+    pub fn display_known_features_notice(&mut self, reasons: &[String]) -> Result<()> {
         if self.is_terminal {
-            self.term.clear_line()?;
+            self.term.clear_to_end_of_screen()?;
         }
         writeln!(self.term)?;
         writeln!(
             self.term,
             "Some features that do not affect compilation but can limit functionally where found. For more information refer to https://github.com/ToBinio/cargo-features-manager#prune"
         )?;
+
+        for reason in reasons {
+            writeln!(self.term, "  {}", style(reason).color256(8))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn display_budget_notice(&mut self, unchecked_count: usize) -> Result<()> {
+        if self.is_terminal {
+            self.term.clear_to_end_of_screen()?;
+        }
+        writeln!(self.term)?;
+        writeln!(
+            self.term,
+            "The budget ran out before all features were checked - {} features are kept without a check.",
+            unchecked_count
+        )?;
+        Ok(())
+    }
+
+    pub fn display_interrupted_notice(&mut self, unchecked_count: usize) -> Result<()> {
+        if self.is_terminal {
+            self.term.clear_to_end_of_screen()?;
+        }
+        writeln!(self.term)?;
+        writeln!(
+            self.term,
+            "Prune was interrupted - {} features were not checked yet. Use `--resume` to continue later on.",
+            unchecked_count
+        )?;
         Ok(())
     }
 
@@ -88,37 +156,36 @@ impl Display {
         package_name: &str,
         package_features: &HashMap<DependencyName, Vec<FeatureName>>,
     ) -> Result<()> {
+        self.package_name = package_name.to_string();
+        self.package_feature_count = package_features.values().flatten().count();
+
         if self.is_terminal.not() {
             return Ok(());
         }
 
-        self.package_name = package_name.to_string();
-        self.package_feature_count = package_features.values().flatten().count();
-        self.package_checked_features_count = 0;
-
         if self.is_workspace {
             let package_inset = self.package_inset;
 
-            self.term.clear_line()?;
+            self.term.clear_to_end_of_screen()?;
             writeln!(self.term)?;
             writeln!(
                 self.term,
                 "{:package_inset$}{} [{}]",
                 "", package_name, self.package_feature_count
             )?;
+
+            self.display_running()?;
         }
 
         Ok(())
     }
 
-    pub fn next_dependency(&mut self, name: &str, features: &[FeatureName]) {
-        self.dependency_feature_count = features.len();
-        self.dependency_name = name.to_string();
-    }
-
     pub fn finish_dependency(
         &mut self,
+        dependency_name: &str,
+        dependency_feature_count: usize,
         features: Vec<(&FeatureName, IsKnownFeature)>,
+        kept_reasons: Vec<(&FeatureName, &String)>,
     ) -> Result<()> {
         let mut disabled_count = style(
             features
@@ -137,60 +204,101 @@ impl Display {
             disabled_count = style("0".to_string());
         }
 
-        let dependency_inset = self.dependency_inset;
+        let (dependency_inset, dependency_name) = match self
+            .plain_paths
+            .get(&(self.package_name.clone(), dependency_name.to_string()))
+        {
+            Some(path) if self.is_plain => (2, path.clone()),
+            _ => (self.dependency_inset, dependency_name.to_string()),
+        };
 
         if self.is_terminal {
-            self.term.clear_line()?;
+            self.term.clear_to_end_of_screen()?;
         }
         writeln!(
             self.term,
             "{:dependency_inset$}{} [{}/{}]",
-            "", self.dependency_name, disabled_count, self.dependency_feature_count
+            "", dependency_name, disabled_count, dependency_feature_count
         )?;
 
-        Ok(())
-    }
-
-    pub fn next_feature(&mut self, id: usize, feature_name: &FeatureName) -> Result<()> {
-        if self.is_terminal.not() {
-            return Ok(());
+        for (feature, reason) in kept_reasons {
+            writeln!(
+                self.term,
+                "{:dependency_inset$} └ {}",
+                "",
+                style(format!("kept `{feature}`: {reason}")).dim()
+            )?;
         }
 
-        let dependency_inset = self.dependency_inset;
-
-        self.term.clear_line()?;
-        writeln!(
-            self.term,
-            "{:dependency_inset$}{} [{}/{}]",
-            "", self.dependency_name, id, self.dependency_feature_count,
-        )?;
-        self.term.clear_line()?;
-        writeln!(self.term, "{:dependency_inset$} └ {}", "", feature_name)?;
-        self.term.move_cursor_up(2)?;
-
-        self.display_progress_bar()?;
+        self.display_running()?;
 
         Ok(())
     }
 
-    pub fn finish_feature(&mut self) -> Result<()> {
-        self.checked_features_count += 1;
-        self.package_checked_features_count += 1;
+    pub fn next_feature(
+        &mut self,
+        worker: usize,
+        job: &Job,
+        feature_names: &[FeatureName],
+    ) -> Result<()> {
+        self.running_features[worker] = Some(RunningFeature {
+            dependency_name: job.dependency_name.clone(),
+            dependency_feature_count: job.features.len(),
+            checked_features_count: job.checked(),
+            feature_names: feature_names.to_vec(),
+        });
+
+        self.display_running()
+    }
 
-        self.display_progress_bar()?;
+    pub fn finish_feature(&mut self, worker: usize, jobs: &[Job]) -> Result<()> {
+        self.running_features[worker] = None;
 
-        Ok(())
+        self.checked_features_count = 0;
+        self.package_checked_features_count.clear();
+
+        for job in jobs {
+            self.checked_features_count += job.checked();
+            *self
+                .package_checked_features_count
+                .entry(job.package_name.clone())
+                .or_default() += job.checked();
+        }
+
+        self.display_running()
     }
 
-    fn display_progress_bar(&mut self) -> Result<()> {
+    /// displays all currently running features and the progress bar below the finished output
+    fn display_running(&mut self) -> Result<()> {
         if self.is_terminal.not() {
             return Ok(());
         }
 
-        self.term.move_cursor_down(2)?;
-        self.term.clear_line()?;
+        let dependency_inset = self.dependency_inset;
+        let mut line_count = 0;
+
+        self.term.clear_to_end_of_screen()?;
+
+        for running in self.running_features.iter().flatten() {
+            writeln!(
+                self.term,
+                "{:dependency_inset$}{} [{}/{}]",
+                "",
+                running.dependency_name,
+                running.checked_features_count,
+                running.dependency_feature_count,
+            )?;
+            writeln!(
+                self.term,
+                "{:dependency_inset$} └ {}",
+                "",
+                running.feature_names.join(", ")
+            )?;
+
+            line_count += 2;
+        }
+
         writeln!(self.term)?;
-        self.term.clear_line()?;
         write!(
             self.term,
             "Workspace [{}/{}]",
@@ -201,13 +309,17 @@ impl Display {
             write!(
                 self.term,
                 " -> {} [{}/{}]",
-                self.package_name, self.package_checked_features_count, self.package_feature_count
+                self.package_name,
+                self.package_checked_features_count
+                    .get(&self.package_name)
+                    .unwrap_or(&0),
+                self.package_feature_count
             )?;
         }
 
         writeln!(self.term)?;
 
-        self.term.move_cursor_up(4)?;
+        self.term.move_cursor_up(line_count + 2)?;
         Ok(())
     }
 }
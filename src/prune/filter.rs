@@ -0,0 +1,306 @@
+use crate::PruneArgs;
+use crate::io::util::glob_matches;
+use crate::project::dependency::{Dependency, DependencyType};
+use crate::project::document::Document;
+use crate::project::package::Package;
+use crate::prune::FeaturesMap;
+use color_eyre::Result;
+use console::{Term, style};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::ops::Not;
+
+/// the kinds of dependencies prune can be limited to
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    /// `[dependencies]` and `[workspace.dependencies]`
+    Normal,
+    /// `[dev-dependencies]`
+    Dev,
+    /// `[build-dependencies]`
+    Build,
+}
+
+impl DependencyKind {
+    /// none if cargo does not know the kind
+    pub fn of(dependency: &Dependency) -> Option<Self> {
+        match dependency.kind {
+            DependencyType::Normal | DependencyType::Workspace => Some(DependencyKind::Normal),
+            DependencyType::Development => Some(DependencyKind::Dev),
+            DependencyType::Build => Some(DependencyKind::Build),
+            DependencyType::Unknown => None,
+        }
+    }
+}
+
+/// limits which packages and dependencies are pruned
+pub struct Filter {
+    packages: Vec<String>,
+    dependencies: Vec<String>,
+    excluded_dependencies: Vec<String>,
+    kinds: Vec<DependencyKind>,
+}
+
+impl Filter {
+    pub fn new(args: &PruneArgs) -> Self {
+        Self {
+            packages: args.packages.clone(),
+            dependencies: args.dependencies.clone(),
+            excluded_dependencies: args.excluded_dependencies.clone(),
+            kinds: args.kinds.clone(),
+        }
+    }
+
+    /// an empty list of globs or kinds matches everything
+    ///
+    /// `package_name` is the plain name - `[workspace]` for `[workspace.dependencies]` like in the output
+    pub fn matches(&self, package_name: &str, dependency: &Dependency) -> bool {
+        let kind = DependencyKind::of(dependency);
+
+        matches_any(&self.packages, package_name)
+            && matches_any(&self.dependencies, &dependency.name)
+            && self
+                .excluded_dependencies
+                .iter()
+                .any(|glob| glob_matches(glob, &dependency.name))
+                .not()
+            && (self.kinds.is_empty() || kind.is_some_and(|kind| self.kinds.contains(&kind)))
+    }
+
+    /// removes all dependencies which do not match from the map
+    pub fn apply(&self, document: &Document, features_to_test: &mut FeaturesMap) -> Result<()> {
+        for (package_name, dependencies) in features_to_test.iter_mut() {
+            let package = document.get_package(package_name)?;
+            let plain_package_name = document.plain_package_name(package_name)?;
+
+            let mut filtered_dependencies = vec![];
+
+            for dependency_name in dependencies.keys() {
+                if self
+                    .matches(&plain_package_name, package.get_dep(dependency_name)?)
+                    .not()
+                {
+                    filtered_dependencies.push(dependency_name.clone());
+                }
+            }
+
+            for dependency_name in filtered_dependencies {
+                dependencies.remove(&dependency_name);
+            }
+        }
+
+        features_to_test.retain(|_, dependencies| dependencies.is_empty().not());
+
+        Ok(())
+    }
+
+    /// the `--package` and `--dependency` globs which match no package or dependency of the project - likely a typo
+    pub fn unmatched_globs(&self, document: &Document) -> Result<Vec<String>> {
+        let mut package_names = vec![];
+
+        for package in document.get_packages() {
+            package_names.push(document.plain_package_name(&package.name)?);
+        }
+
+        let dependency_names = document
+            .get_packages()
+            .iter()
+            .flat_map(Package::get_deps)
+            .map(|dependency| &dependency.name)
+            .collect::<Vec<_>>();
+
+        let packages = self
+            .packages
+            .iter()
+            .filter(|glob| {
+                package_names
+                    .iter()
+                    .any(|name| glob_matches(glob, name))
+                    .not()
+            })
+            .map(|glob| format!("--package {glob}"));
+
+        let dependencies = self
+            .dependencies
+            .iter()
+            .filter(|glob| {
+                dependency_names
+                    .iter()
+                    .any(|name| glob_matches(glob, name))
+                    .not()
+            })
+            .map(|glob| format!("--dependency {glob}"));
+
+        Ok(packages.chain(dependencies).collect())
+    }
+}
+
+pub fn display_unmatched_globs(unmatched_globs: &[String]) -> Result<()> {
+    let term = Term::stdout();
+
+    for glob in unmatched_globs {
+        writeln!(
+            &term,
+            "{}",
+            style(format!("warning: `{glob}` matches nothing")).yellow()
+        )?;
+    }
+
+    if unmatched_globs.is_empty().not() {
+        writeln!(&term)?;
+    }
+
+    Ok(())
+}
+
+fn matches_any(globs: &[String], name: &str) -> bool {
+    globs.is_empty() || globs.iter().any(|glob| glob_matches(glob, name))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::document::Document;
+    use crate::project::fixture::{app_with_dep, workspace_with_dep};
+    use crate::prune::FeaturesMap;
+    use crate::prune::filter::{DependencyKind, Filter};
+    use std::collections::HashMap;
+
+    /// `dep` as a normal, dev- and build-dependency of `app`
+    fn filtered(filter: Filter) -> Vec<DependencyKind> {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }
+
+[dev-dependencies]
+dep = { path = "../dep", features = ["b"] }
+
+[build-dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let mut features_to_test: FeaturesMap = HashMap::from([(
+            "app".to_string(),
+            document
+                .get_package("app")
+                .unwrap()
+                .get_deps()
+                .iter()
+                .map(|dependency| (dependency.get_name(), vec!["b".to_string()]))
+                .collect(),
+        )]);
+
+        filter.apply(&document, &mut features_to_test).unwrap();
+
+        let package = document.get_package("app").unwrap();
+
+        let mut kinds = features_to_test
+            .values()
+            .flat_map(|dependencies| dependencies.keys())
+            .map(|name| DependencyKind::of(package.get_dep(name).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        kinds.sort();
+
+        kinds
+    }
+
+    fn filter(dependencies: &[&str], excluded: &[&str], kinds: Vec<DependencyKind>) -> Filter {
+        Filter {
+            packages: vec![],
+            dependencies: dependencies.iter().map(|glob| glob.to_string()).collect(),
+            excluded_dependencies: excluded.iter().map(|glob| glob.to_string()).collect(),
+            kinds,
+        }
+    }
+
+    #[test]
+    fn an_empty_filter_keeps_everything() {
+        assert_eq!(
+            filtered(filter(&[], &[], vec![])),
+            [
+                DependencyKind::Normal,
+                DependencyKind::Dev,
+                DependencyKind::Build
+            ]
+        );
+    }
+
+    #[test]
+    fn excluded_dependencies_win_over_included_ones() {
+        assert!(filtered(filter(&["de*"], &["dep"], vec![])).is_empty());
+    }
+
+    #[test]
+    fn only_the_given_kinds_are_kept() {
+        assert_eq!(
+            filtered(filter(&["dep"], &[], vec![DependencyKind::Dev])),
+            [DependencyKind::Dev]
+        );
+        assert_eq!(
+            filtered(filter(
+                &[],
+                &[],
+                vec![DependencyKind::Normal, DependencyKind::Build]
+            )),
+            [DependencyKind::Normal, DependencyKind::Build]
+        );
+    }
+
+    #[test]
+    fn the_workspace_is_matched_by_its_plain_name() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", features = ["b"] }"#,
+            "{ workspace = true }",
+        );
+        let document = Document::new(dir.path()).unwrap();
+
+        let filter = Filter {
+            packages: vec!["[workspace]".to_string()],
+            ..filter(&[], &[], vec![])
+        };
+
+        let mut features_to_test: FeaturesMap = document
+            .get_packages()
+            .iter()
+            .map(|package| {
+                let dependencies = package
+                    .get_deps()
+                    .iter()
+                    .map(|dependency| (dependency.get_name(), vec!["b".to_string()]))
+                    .collect();
+
+                (package.name.clone(), dependencies)
+            })
+            .collect();
+
+        filter.apply(&document, &mut features_to_test).unwrap();
+
+        let workspace_name = &document.get_workspace_package().unwrap().unwrap().name;
+        assert_eq!(
+            features_to_test.keys().collect::<Vec<_>>(),
+            [workspace_name]
+        );
+        assert!(filter.unmatched_globs(&document).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unmatched_globs_are_found() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let filter = Filter {
+            packages: vec!["ap*".to_string(), "Workspace".to_string()],
+            ..filter(&["dep", "serde*"], &[], vec![])
+        };
+
+        assert_eq!(
+            filter.unmatched_globs(&document).unwrap(),
+            ["--package Workspace", "--dependency serde*"]
+        );
+    }
+}
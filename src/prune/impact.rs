@@ -0,0 +1,396 @@
+use crate::ImpactArgs;
+use crate::io::backup::ManifestBackup;
+use crate::io::util::{format_size, format_size_delta};
+use crate::project::document::Document;
+use crate::prune::check::clean;
+use crate::prune::parse::get_features_to_test;
+use crate::prune::report::DependencyId;
+use crate::prune::{FeatureName, INTERRUPTED, PackageName, create_copies, with_features_disabled};
+use cargo_metadata::Message;
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, bail};
+use console::{Term, style};
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{BufReader, Write};
+use std::path::{self, Path};
+use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+/// a build of the project
+struct Measurement {
+    duration: Duration,
+    crates: BTreeSet<String>,
+    /// the summed up size of all binaries
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct Report {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline_bytes: Option<u64>,
+    baseline_crates: usize,
+    features: Vec<FeatureImpact>,
+}
+
+#[derive(Serialize)]
+struct FeatureImpact {
+    /// e.g. `app 🦀 serde/std` - the display names depend on the terminal and are only printed
+    #[serde(skip)]
+    display_name: String,
+    package: PackageName,
+    dependency: DependencyId,
+    feature: FeatureName,
+    /// the project does not build without the feature
+    is_required: bool,
+    /// how much longer the build takes with the feature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<f64>,
+    /// how many bytes the feature adds to the release binaries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<i64>,
+    /// crates which are only compiled because of the feature
+    extra_crates: Vec<String>,
+}
+
+impl FeatureImpact {
+    /// a required feature until it was measured
+    fn new(
+        document: &Document,
+        package_name: &str,
+        dependency_name: &str,
+        feature: FeatureName,
+    ) -> Result<Self> {
+        let (package, dependency) = DependencyId::new(document, package_name, dependency_name)?;
+
+        Ok(Self {
+            display_name: format!("{package_name} {dependency_name}/{feature}"),
+            package,
+            dependency,
+            feature,
+            is_required: true,
+            duration_secs: None,
+            size_bytes: None,
+            extra_crates: vec![],
+        })
+    }
+}
+
+/// builds the project without each enabled feature and compares it to a build with all of them
+pub fn impact(args: &ImpactArgs) -> Result<()> {
+    let main_document = Document::new(".")?;
+
+    //needed to be bound here so the temp_dirs live long enough
+    let (_tmp_dirs, mut documents) = if args.no_tmp {
+        (vec![], vec![Document::new(".")?])
+    } else {
+        create_copies(&main_document, 1)?
+    };
+
+    // the features are toggled in the manifests of the project itself with --no-tmp
+    let _backup = args
+        .no_tmp
+        .then(|| ManifestBackup::new(&main_document))
+        .transpose()?;
+
+    let document = &mut documents[0];
+
+    // every build starts from scratch - so the project's own target dir is left alone
+    let target_dir = path::absolute(main_document.root_path())?
+        .join("target")
+        .join("cargo-features-manager")
+        .join("impact");
+
+    let (features_to_test, _) = get_features_to_test(document, args.only_dependency, false)?;
+
+    let features = features_to_test
+        .into_iter()
+        .flat_map(|(package_name, dependencies)| {
+            dependencies
+                .into_iter()
+                .flat_map(move |(dependency_name, features)| {
+                    let package_name = package_name.clone();
+
+                    features.into_iter().map(move |feature| {
+                        (package_name.clone(), dependency_name.clone(), feature)
+                    })
+                })
+        })
+        .sorted()
+        .collect_vec();
+
+    let progress = ProgressBar::new_spinner().with_message("measuring the baseline build");
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    let Some(baseline) = measure(document.root_path(), &target_dir, args.size)? else {
+        progress.finish_and_clear();
+        bail!("the project does not build");
+    };
+
+    if args.size && baseline.size == 0 {
+        progress.finish_and_clear();
+        bail!("the project has no binaries");
+    }
+
+    let count = features.len();
+    let mut impacts = vec![];
+
+    for (index, (package_name, dependency_name, feature)) in features.into_iter().enumerate() {
+        progress.set_message(format!(
+            "measuring {dependency_name}/{feature} [{}/{count}]",
+            index + 1
+        ));
+
+        let measurement = with_features_disabled(
+            document,
+            &package_name,
+            &dependency_name,
+            std::slice::from_ref(&feature),
+            |document| measure(document.root_path(), &target_dir, args.size),
+        )?;
+
+        let impact = FeatureImpact::new(document, &package_name, &dependency_name, feature)?;
+
+        impacts.push(match measurement {
+            Some(measurement) => FeatureImpact {
+                is_required: false,
+                // incremental release builds say nothing about the build time
+                duration_secs: (!args.size).then_some(
+                    baseline.duration.as_secs_f64() - measurement.duration.as_secs_f64(),
+                ),
+                size_bytes: args
+                    .size
+                    .then(|| baseline.size as i64 - measurement.size as i64),
+                extra_crates: baseline
+                    .crates
+                    .difference(&measurement.crates)
+                    .cloned()
+                    .collect(),
+                ..impact
+            },
+            None => impact,
+        });
+    }
+
+    progress.finish_and_clear();
+
+    // the most expensive features first
+    impacts.sort_by(|a, b| {
+        a.is_required
+            .cmp(&b.is_required)
+            .then(b.size_bytes.cmp(&a.size_bytes))
+            .then(
+                b.duration_secs
+                    .unwrap_or_default()
+                    .total_cmp(&a.duration_secs.unwrap_or_default()),
+            )
+    });
+
+    let report = Report {
+        baseline_secs: (!args.size).then_some(baseline.duration.as_secs_f64()),
+        baseline_bytes: args.size.then_some(baseline.size),
+        baseline_crates: baseline.crates.len(),
+        features: impacts,
+    };
+
+    display_report(&report)?;
+
+    if let Some(path) = &args.report {
+        fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+
+    Ok(())
+}
+
+/// returns None if the project does not build
+///
+/// `size` builds the binaries in release incrementally instead of doing a clean debug build
+fn measure(path: &Path, target_dir: &Path, size: bool) -> Result<Option<Measurement>> {
+    let mut args = vec!["build", "--workspace", "--message-format=json"];
+
+    if size {
+        args.extend(["--release", "--bins"]);
+    } else {
+        clean(path, Some(target_dir))?;
+    }
+
+    let start = Instant::now();
+
+    let mut child = Command::new("cargo")
+        .current_dir(path)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("could not read the output of cargo")?;
+
+    let mut crates = BTreeSet::new();
+    let mut executables = vec![];
+
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        if let Message::CompilerArtifact(artifact) = message?
+            && !artifact.target.is_custom_build()
+        {
+            if artifact.target.is_bin()
+                && let Some(executable) = artifact.executable
+            {
+                executables.push(executable);
+            }
+
+            crates.insert(artifact.target.name);
+        }
+    }
+
+    let status = child.wait()?;
+    let duration = start.elapsed();
+
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        bail!("interrupted");
+    }
+
+    if !status.success() {
+        return Ok(None);
+    }
+
+    let mut size = 0;
+
+    for executable in executables {
+        size += fs::metadata(executable)?.len();
+    }
+
+    Ok(Some(Measurement {
+        duration,
+        crates,
+        size,
+    }))
+}
+
+fn display_report(report: &Report) -> Result<()> {
+    let term = Term::stdout();
+
+    if let Some(bytes) = report.baseline_bytes {
+        writeln!(
+            &term,
+            "baseline release binaries: {}, {} crates",
+            format_size(bytes),
+            report.baseline_crates
+        )?;
+    } else {
+        writeln!(
+            &term,
+            "baseline build: {:.1}s, {} crates",
+            report.baseline_secs.unwrap_or_default(),
+            report.baseline_crates
+        )?;
+    }
+    writeln!(&term)?;
+
+    writeln!(
+        &term,
+        "impact of enabled features [{}]",
+        report.features.len()
+    )?;
+
+    let names = report
+        .features
+        .iter()
+        .map(|impact| &impact.display_name)
+        .collect_vec();
+
+    let width = names
+        .iter()
+        .map(|name| console::measure_text_width(name))
+        .max()
+        .unwrap_or_default();
+
+    for (name, impact) in names.iter().zip(&report.features) {
+        let padding = " ".repeat(width - console::measure_text_width(name));
+
+        if impact.is_required {
+            writeln!(&term, "  {name}{padding}  {}", style("required").dim())?;
+            continue;
+        }
+
+        writeln!(
+            &term,
+            "  {name}{padding}  {}  {}",
+            style(match impact.size_bytes {
+                Some(bytes) => format_size_delta(bytes),
+                None => format!("{:+.1}s", impact.duration_secs.unwrap_or_default()),
+            })
+            .yellow(),
+            style(format!("+{} crates", impact.extra_crates.len())).yellow()
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::document::Document;
+    use crate::project::fixture::workspace_with_dep;
+    use crate::prune::impact::FeatureImpact;
+    use std::ops::Not;
+
+    #[test]
+    fn the_report_uses_plain_names() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", features = ["b"] }"#,
+            r#"{ workspace = true }
+
+[dev-dependencies]
+dep = { path = "../dep", features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path()).unwrap();
+
+        let workspace_name = &document.get_workspace_package().unwrap().unwrap().name;
+        let dev_name = document
+            .get_package("app")
+            .unwrap()
+            .get_deps()
+            .iter()
+            .find(|dependency| dependency.workspace.not())
+            .unwrap()
+            .get_name();
+
+        let workspace =
+            FeatureImpact::new(&document, workspace_name, "dep", "b".to_string()).unwrap();
+        let dev = FeatureImpact::new(&document, "app", &dev_name, "b".to_string()).unwrap();
+
+        let json = serde_json::to_value([workspace, dev]).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "package": "[workspace]",
+                    "dependency": { "name": "dep", "kind": "normal" },
+                    "feature": "b",
+                    "is_required": true,
+                    "extra_crates": [],
+                },
+                {
+                    "package": "app",
+                    "dependency": { "name": "dep", "kind": "dev" },
+                    "feature": "b",
+                    "is_required": true,
+                    "extra_crates": [],
+                },
+            ])
+        );
+    }
+}
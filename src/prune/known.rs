@@ -0,0 +1,352 @@
+use crate::io::util::{get_item_from_doc, toml_document_from_path};
+use crate::project::dependency::Dependency;
+use crate::prune::check::CONFIG_PATHS;
+use crate::prune::{DependencyName, FeatureName};
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, WrapErr};
+use console::{Term, style};
+use itertools::Itertools;
+use semver::{Version, VersionReq};
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use toml_edit::TableLike;
+
+/// the name of the user and the project file
+const FILE_NAME: &str = "known-features.toml";
+
+/// where a known feature is defined
+#[derive(Clone, Debug)]
+pub enum KnownFeatureSource {
+    /// the Known-Features.toml shipped with cargo-features-manager
+    BuiltIn,
+    File(PathBuf),
+    /// a table inside of a Cargo.toml
+    Metadata {
+        manifest: PathBuf,
+        table: String,
+    },
+}
+
+impl Display for KnownFeatureSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KnownFeatureSource::BuiltIn => f.write_str("built-in"),
+            KnownFeatureSource::File(path) => write!(f, "{}", path.display()),
+            KnownFeatureSource::Metadata { manifest, table } => {
+                write!(f, "{} [{table}]", manifest.display())
+            }
+        }
+    }
+}
+
+/// a feature which does not affect compilation but removes functionality
+pub struct KnownFeature {
+    pub dependency: DependencyName,
+    pub feature: FeatureName,
+    /// the versions of the dependency the entry applies to - all if none
+    pub version: Option<VersionReq>,
+    pub reason: Option<String>,
+    pub source: KnownFeatureSource,
+}
+
+impl KnownFeature {
+    fn applies_to(&self, dependency: &Dependency) -> bool {
+        if self.dependency != dependency.name {
+            return false;
+        }
+
+        let Some(requirement) = &self.version else {
+            return true;
+        };
+
+        // the version from the Cargo.lock - otherwise the lowest version the Cargo.toml allows
+        let version = dependency
+            .resolved_version
+            .as_deref()
+            .and_then(|version| Version::parse(version).ok())
+            .or_else(|| lowest_version(&dependency.version));
+
+        // keeping a feature is the safe choice if the version is unknown
+        version.is_none_or(|version| requirement.matches(&version))
+    }
+}
+
+pub struct KnownFeatures {
+    features: Vec<KnownFeature>,
+}
+
+impl KnownFeatures {
+    /// merges the built-in list with the one of the user, the project file and the Cargo.toml of the project
+    pub fn load<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        let root_path = root_path.as_ref();
+
+        let mut features = parse_table(
+            include_str!("../../Known-Features.toml")
+                .parse::<toml_edit::DocumentMut>()?
+                .as_table(),
+            &KnownFeatureSource::BuiltIn,
+        )?;
+
+        let files = user_file().into_iter().chain([root_path.join(FILE_NAME)]);
+
+        for path in files.filter(|path| path.is_file()) {
+            let document = toml_document_from_path(&path)?;
+
+            features.extend(
+                parse_table(document.as_table(), &KnownFeatureSource::File(path.clone()))
+                    .wrap_err(format!("could not parse {}", path.display()))?,
+            );
+        }
+
+        let manifest = root_path.join("Cargo.toml");
+
+        if let Ok(document) = toml_document_from_path(&manifest) {
+            for path in CONFIG_PATHS {
+                let table = format!("{path}.known-features");
+
+                let Ok(item) = get_item_from_doc(&table, &document) else {
+                    continue;
+                };
+
+                let source = KnownFeatureSource::Metadata {
+                    manifest: manifest.clone(),
+                    table: table.clone(),
+                };
+
+                features.extend(parse_table(
+                    item.as_table_like()
+                        .context(format!("could not parse {table} - expected a table"))?,
+                    &source,
+                )?);
+            }
+        }
+
+        Ok(Self { features })
+    }
+
+    pub fn features(&self) -> &[KnownFeature] {
+        &self.features
+    }
+
+    /// the entries which apply to the version of the dependency - no matter which source defined them
+    pub fn of_dependency(&self, dependency: &Dependency) -> Vec<&KnownFeature> {
+        self.features
+            .iter()
+            .filter(|known| known.applies_to(dependency))
+            .collect()
+    }
+}
+
+/// `~/.config/cargo-features-manager/known-features.toml` or the equivalent of the platform
+fn user_file() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(config_dir.join("cargo-features-manager").join(FILE_NAME))
+}
+
+/// the lowest of the versions the comparators start at which the whole requirement allows
+fn lowest_version(requirement: &str) -> Option<Version> {
+    let requirement = VersionReq::parse(requirement).ok()?;
+
+    requirement
+        .comparators
+        .iter()
+        .map(|comparator| {
+            Version::new(
+                comparator.major,
+                comparator.minor.unwrap_or_default(),
+                comparator.patch.unwrap_or_default(),
+            )
+        })
+        .filter(|version| requirement.matches(version))
+        .min()
+}
+
+/// a table of `dependency = ["feature", ...]` entries for all versions or
+/// `[dependency."<version requirement>"]` tables of `feature = "reason"` entries
+fn parse_table(table: &dyn TableLike, source: &KnownFeatureSource) -> Result<Vec<KnownFeature>> {
+    let mut features = vec![];
+
+    for (dependency, item) in table.iter() {
+        if let Some(entries) = item.as_array() {
+            for feature in entries.iter().filter_map(|entry| entry.as_str()) {
+                features.push(KnownFeature {
+                    dependency: dependency.to_string(),
+                    feature: feature.to_string(),
+                    version: None,
+                    reason: None,
+                    source: source.clone(),
+                });
+            }
+
+            continue;
+        }
+
+        let versions = item.as_table_like().context(format!(
+            "could not parse the known features of {dependency} - expected an array or a table"
+        ))?;
+
+        for (requirement, entries) in versions.iter() {
+            let version = VersionReq::parse(requirement).wrap_err(format!(
+                "could not parse the version \"{requirement}\" of {dependency}"
+            ))?;
+
+            let entries = entries.as_table_like().context(format!(
+                "could not parse the known features of {dependency} {requirement} - expected a table"
+            ))?;
+
+            for (feature, reason) in entries.iter() {
+                features.push(KnownFeature {
+                    dependency: dependency.to_string(),
+                    feature: feature.to_string(),
+                    version: Some(version.clone()),
+                    reason: Some(
+                        reason
+                            .as_str()
+                            .context(format!(
+                                "could not parse the reason of {dependency}/{feature} - expected a string"
+                            ))?
+                            .to_string(),
+                    ),
+                    source: source.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(features)
+}
+
+pub fn display_known_features(known_features: &KnownFeatures) -> Result<()> {
+    let term = Term::stdout();
+
+    writeln!(
+        &term,
+        "known features [{}]",
+        known_features.features().len()
+    )?;
+
+    let name = |known: &KnownFeature| match &known.version {
+        Some(version) => format!("{} ({version})", known.feature),
+        None => known.feature.clone(),
+    };
+
+    let width = known_features
+        .features()
+        .iter()
+        .map(|known| name(known).len())
+        .max()
+        .unwrap_or_default();
+
+    for (dependency, features) in &known_features
+        .features()
+        .iter()
+        .sorted_by(|a, b| a.dependency.cmp(&b.dependency))
+        .chunk_by(|known| &known.dependency)
+    {
+        writeln!(&term, "  {dependency}")?;
+
+        for known in features {
+            writeln!(
+                &term,
+                "    {:width$}  {}",
+                name(known),
+                style(&known.source).color256(8)
+            )?;
+
+            if let Some(reason) = &known.reason {
+                writeln!(&term, "      {}", style(format!("└ {reason}")).color256(8))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::dependency::{Dependency, DependencyType};
+    use crate::prune::known::{KnownFeatureSource, parse_table};
+    use std::collections::HashMap;
+    use std::ops::Not;
+
+    fn dependency(version: &str, resolved_version: Option<&str>) -> Dependency {
+        Dependency {
+            name: "hyper".to_string(),
+            rename: None,
+            comment: None,
+            version: version.to_string(),
+            resolved_version: resolved_version.map(|version| version.to_string()),
+            workspace: false,
+            kind: DependencyType::Normal,
+            target: None,
+            features: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn versioned_entries_apply_to_matching_versions() {
+        let document: toml_edit::DocumentMut = r#"
+            serde_json = ["preserve_order"]
+
+            [hyper."^1"]
+            http2 = "enables HTTP/2 negotiation at runtime"
+
+            [hyper."^0.14"]
+            runtime = "spawns the tokio runtime"
+        "#
+        .parse()
+        .unwrap();
+
+        let features = parse_table(document.as_table(), &KnownFeatureSource::BuiltIn).unwrap();
+
+        assert_eq!(features.len(), 3);
+        assert!(features[0].version.is_none() && features[0].reason.is_none());
+        assert_eq!(
+            features[1].reason.as_deref(),
+            Some("enables HTTP/2 negotiation at runtime")
+        );
+
+        let applying = |dependency: &Dependency| {
+            features
+                .iter()
+                .filter(|known| known.applies_to(dependency))
+                .map(|known| known.feature.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(applying(&dependency("1", Some("1.4.1"))), ["http2"]);
+        assert_eq!(applying(&dependency("0.14", Some("0.14.30"))), ["runtime"]);
+        // without a Cargo.lock the lowest version allowed by the Cargo.toml is used
+        assert_eq!(applying(&dependency("0.14.2", None)), ["runtime"]);
+        assert_eq!(applying(&dependency("2", None)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn every_comparator_is_used() {
+        let document: toml_edit::DocumentMut = r#"
+            [hyper.">=0.14.2, <1"]
+            runtime = "spawns the tokio runtime"
+        "#
+        .parse()
+        .unwrap();
+
+        let features = parse_table(document.as_table(), &KnownFeatureSource::BuiltIn).unwrap();
+
+        let applies = |dependency: &Dependency| features[0].applies_to(dependency);
+
+        assert!(applies(&dependency("0.14", Some("0.14.30"))));
+        assert!(applies(&dependency("0.14", Some("0.14.2"))));
+        assert!(applies(&dependency("0.14", Some("0.14.1"))).not());
+        assert!(applies(&dependency("1", Some("1.0.0"))).not());
+        // the lowest version the Cargo.toml allows is the start of its second comparator
+        assert!(applies(&dependency("<0.15, >=0.14.3", None)));
+        assert!(applies(&dependency("<0.15, >=0.13", None)).not());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::project::package::Package;
+use color_eyre::Result;
+use color_eyre::eyre::bail;
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+
+/// which configurations of the project's own features are checked
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
+pub enum FeatureMatrix {
+    /// the default features
+    Default,
+    /// `--all-features`
+    All,
+    /// `--no-default-features`
+    None,
+    /// every feature on its own with `--no-default-features`
+    Each,
+}
+
+/// a single configuration of the project's own features
+#[derive(Debug, PartialEq)]
+pub enum FeatureSet {
+    Default,
+    All,
+    None,
+    Only { package: String, feature: String },
+}
+
+impl FeatureSet {
+    pub fn cargo_args(&self) -> Vec<String> {
+        match self {
+            FeatureSet::Default => vec![],
+            FeatureSet::All => vec!["--all-features".to_string()],
+            FeatureSet::None => vec!["--no-default-features".to_string()],
+            FeatureSet::Only { package, feature } => vec![
+                "--no-default-features".to_string(),
+                "--features".to_string(),
+                format!("{package}/{feature}"),
+            ],
+        }
+    }
+}
+
+impl Display for FeatureSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureSet::Default => f.write_str("default features"),
+            FeatureSet::All => f.write_str("all features"),
+            FeatureSet::None => f.write_str("no default features"),
+            FeatureSet::Only { package, feature } => write!(f, "only {package}/{feature}"),
+        }
+    }
+}
+
+/// a check without any feature set runs nothing - so the matrix has to result in at least one
+pub fn feature_sets(matrix: &[FeatureMatrix], packages: &[Package]) -> Result<Vec<FeatureSet>> {
+    if matrix.is_empty() {
+        bail!("the feature matrix is empty - prune needs at least one entry");
+    }
+
+    let sets = matrix
+        .iter()
+        .flat_map(|entry| match entry {
+            FeatureMatrix::Default => vec![FeatureSet::Default],
+            FeatureMatrix::All => vec![FeatureSet::All],
+            FeatureMatrix::None => vec![FeatureSet::None],
+            FeatureMatrix::Each => {
+                let sets = packages
+                    .iter()
+                    .flat_map(|package| {
+                        package
+                            .features
+                            .iter()
+                            .sorted()
+                            .map(|feature| FeatureSet::Only {
+                                package: package.name.clone(),
+                                feature: feature.clone(),
+                            })
+                    })
+                    .collect_vec();
+
+                // without features of its own the project can only be checked as it is
+                if sets.is_empty() {
+                    vec![FeatureSet::Default]
+                } else {
+                    sets
+                }
+            }
+        })
+        .fold(vec![], |mut sets, set| {
+            if !sets.contains(&set) {
+                sets.push(set);
+            }
+            sets
+        });
+
+    Ok(sets)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::package::Package;
+    use crate::prune::matrix::{FeatureMatrix, FeatureSet, feature_sets};
+
+    fn package(name: &str, features: &[&str]) -> Package {
+        Package {
+            dependencies: vec![],
+            name: name.to_string(),
+            features: features.iter().map(|feature| feature.to_string()).collect(),
+            manifest_path: format!("{name}/Cargo.toml"),
+        }
+    }
+
+    fn only(package: &str, feature: &str) -> FeatureSet {
+        FeatureSet::Only {
+            package: package.to_string(),
+            feature: feature.to_string(),
+        }
+    }
+
+    #[test]
+    fn each_feature_of_every_package_is_checked_on_its_own() {
+        let packages = [package("a", &["std", "serde"]), package("b", &["extra"])];
+
+        let sets = feature_sets(&[FeatureMatrix::Each], &packages).unwrap();
+
+        assert_eq!(
+            sets,
+            [only("a", "serde"), only("a", "std"), only("b", "extra")]
+        );
+    }
+
+    #[test]
+    fn duplicate_entries_are_checked_once() {
+        let sets = feature_sets(
+            &[
+                FeatureMatrix::Default,
+                FeatureMatrix::All,
+                FeatureMatrix::Default,
+            ],
+            &[package("a", &[])],
+        )
+        .unwrap();
+
+        assert_eq!(sets, [FeatureSet::Default, FeatureSet::All]);
+    }
+
+    #[test]
+    fn each_without_features_falls_back_to_the_default_features() {
+        let packages = [package("a", &[])];
+
+        assert_eq!(
+            feature_sets(&[FeatureMatrix::Each], &packages).unwrap(),
+            [FeatureSet::Default]
+        );
+        assert_eq!(
+            feature_sets(&[FeatureMatrix::Default, FeatureMatrix::Each], &packages).unwrap(),
+            [FeatureSet::Default]
+        );
+    }
+
+    #[test]
+    fn empty_matrix_is_rejected() {
+        assert!(feature_sets(&[], &[package("a", &["std"])]).is_err());
+    }
+}
@@ -1,286 +1,844 @@
-use crate::io::save::save_dependency;
+use crate::io::backup::ManifestBackup;
+use crate::io::save::{remove_dependency, save_dependency, save_kept_feature};
 use crate::project::dependency::Dependency;
 use crate::project::document::Document;
+use crate::prune::cache::{Cache, CacheKey};
+use crate::prune::check::Checker;
+use crate::prune::checkpoint::{Checkpoint, Verdict};
 use crate::prune::display::Display;
+use crate::prune::filter::{Filter, display_unmatched_globs};
+use crate::prune::known::KnownFeatures;
 use crate::prune::parse::get_features_to_test;
-use crate::{CleanLevel, PruneArgs};
+use crate::prune::report::Report;
+use crate::prune::review::{Decision, Removal, Review};
+use crate::prune::scheduler::{Job, Scheduler, SharedScheduler};
+use crate::prune::target::Target;
+use crate::prune::unification::{UnifiedFeatures, display_unified_features};
+use crate::prune::unused::{
+    UnusedDependency, display_unused_dependencies, find_unused_dependencies,
+};
+use crate::prune::verify::{display_conflicts, verify_removals};
+use crate::prune::worker::{Worker, WorkerEvent};
+use crate::{CleanLevel, PruneArgs, PruneStrategy};
 use color_eyre::Result;
-use color_eyre::eyre::{ContextCompat, eyre};
-use dircpy::copy_dir;
+use color_eyre::eyre::{bail, eyre};
+use console::Term;
 use indicatif::ProgressBar;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Write;
 use std::ops::Not;
-use std::path::Path;
-use std::process::{Command, Stdio};
-use std::time::Duration;
+use std::path::{self, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use tempfile::TempDir;
 
 mod parse;
 
+pub mod impact;
+
+pub mod check;
+
+pub mod filter;
+
+mod checkpoint;
+
+mod cache;
+
+pub mod report;
+
 mod display;
 
-type PackageName = String;
+pub mod known;
+
+pub mod matrix;
+
+mod review;
+
+mod scheduler;
+
+mod strategy;
+
+mod target;
+
+mod unification;
+
+mod unused;
+
+mod verify;
+
+mod worker;
+
+const TMP_DIR_PREFIX: &str = ".cargo-features-manager-";
+
+/// set once the user pressed ctrl-c so running checks can be stopped
+pub static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub type PackageName = String;
 pub type DependencyName = String;
 pub type FeatureName = String;
 pub type FeaturesMap = HashMap<PackageName, HashMap<DependencyName, Vec<FeatureName>>>;
 
-pub fn prune(args: &PruneArgs) -> Result<()> {
+/// returns false if `--check` found features which can be removed
+pub fn prune(args: &PruneArgs) -> Result<bool> {
+    if args.jobs == 0 {
+        bail!("--jobs has to be at least 1");
+    }
+
+    if args.review && Term::stdout().is_term().not() {
+        bail!("--review needs an interactive terminal");
+    }
+
+    // the output of --check ends up in CI logs
+    if args.check {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+
     let mut main_document = Document::new(".")?;
 
-    //needed to be set here so the temp_dir lives long enough
-    let tmp_dir = TempDir::with_prefix_in(".cargo-features-manager-", ".")?;
+    // --no-tmp edits the manifests of the project itself - they are restored if prune does not finish
+    let backup = args
+        .no_tmp
+        .then(|| ManifestBackup::new(&main_document))
+        .transpose()?;
 
-    let mut document = if args.no_tmp {
-        Document::new(".")?
+    //needed to be bound here so the temp_dirs live long enough
+    let (_tmp_dirs, mut documents) = if args.no_tmp {
+        (vec![], vec![Document::new(".")?])
     } else {
-        let progress = ProgressBar::new_spinner().with_message("Creating temporary project...");
-        progress.enable_steady_tick(Duration::from_millis(100));
+        create_copies(&main_document, args.jobs)?
+    };
+
+    let checker = Checker::new(args, &main_document)?;
+
+    let (mut features_to_test, kept_by_config) =
+        get_features_to_test(&documents[0], args.only_dependency, args.minimal_defaults)?;
+
+    remove_foreign_dependencies(&documents[0], &mut features_to_test, checker.targets())?;
 
-        let project_path = tmp_dir.path();
-        copy_dir(main_document.root_path(), project_path)?;
+    let filter = Filter::new(args);
+    display_unmatched_globs(&filter.unmatched_globs(&documents[0])?)?;
+    filter.apply(&documents[0], &mut features_to_test)?;
 
-        progress.finish_and_clear();
+    // disabling features another crate enables as well has no effect
+    let unified_features = UnifiedFeatures::find(&documents[0], &features_to_test)?;
 
-        match Document::new(project_path) {
-            Ok(document) => {document}
-            Err(err) => {
-                return Err(err.wrap_err("Failed to create the temporary project - try to use cargo `features prune --no-tmp`"))
+    if unified_features.is_empty().not() {
+        display_unified_features(&unified_features)?;
+        unified_features.apply(&documents[0], &mut features_to_test)?;
+    }
+
+    // the copies are built with a persistent target dir - --no-tmp just uses the one of the project
+    let cache_dir = match &args.cache_dir {
+        _ if args.no_tmp => None,
+        Some(cache_dir) => Some(path::absolute(cache_dir)?),
+        None => Some(
+            path::absolute(main_document.root_path())?
+                .join("target")
+                .join("cargo-features-manager"),
+        ),
+    };
+
+    let mut unused_dependencies = if args.unused {
+        let (unused_dependencies, skipped_dependencies) = find_unused_dependencies(
+            &documents[0],
+            &checker,
+            &filter,
+            worker_target_dir(cache_dir.as_ref(), 0).as_deref(),
+        )?;
+
+        display_unused_dependencies(&unused_dependencies, &skipped_dependencies)?;
+
+        // there is no need to check the features of dependencies which get removed
+        for unused in &unused_dependencies {
+            if let Some(dependencies) = features_to_test.get_mut(&unused.package_name) {
+                dependencies.remove(&unused.dependency_name);
             }
         }
+
+        unused_dependencies
+    } else {
+        vec![]
+    };
+
+    let checkpoint_path = Checkpoint::path(&main_document);
+    let fingerprint = Checkpoint::fingerprint(&main_document, &checker.description())?;
+
+    let mut verdicts = if args.resume {
+        let verdicts = Checkpoint::load(&checkpoint_path, &fingerprint)?;
+
+        if verdicts.is_none() {
+            println!(
+                "No checkpoint matching the current project was found - starting from the beginning"
+            );
+        }
+
+        verdicts.unwrap_or_default()
+    } else {
+        vec![]
     };
 
-    let features_to_test = get_features_to_test(&document, args.only_dependency)?;
+    let cache_path = Cache::path(&main_document);
+    // with --no-cache the verdicts of this run still replace the cached ones
+    let mut cache = Cache::load(&cache_path);
+
+    let cache_keys = cache_keys(&main_document, &features_to_test, &checker)?;
+
+    // dependencies which did not change since the last run do not need to be checked again
+    for ((package_name, dependency_name), key) in &cache_keys {
+        let is_resumed = verdicts
+            .iter()
+            .any(|verdict| verdict.is_for(package_name, dependency_name));
+
+        if is_resumed || args.no_cache {
+            continue;
+        }
+
+        if let Some(cached_verdicts) = cache.verdicts(key) {
+            // the names of the cached verdicts depend on the locale of the previous run
+            verdicts.extend(cached_verdicts.iter().map(|verdict| Verdict {
+                package: package_name.clone(),
+                dependency: dependency_name.clone(),
+                ..verdict.clone()
+            }));
+        }
+    }
 
     let mut pruner = Pruner {
-        skip_tests: args.skip_tests,
+        checker,
+        budget: args.budget,
+        cache_dir,
         clean_level: args.clean.clone(),
-        document: &mut document,
-        known_features: known_features()?,
+        // ddmin starts with all default features disabled and adds back a minimal set
+        strategy: if args.minimal_defaults {
+            PruneStrategy::Ddmin
+        } else {
+            args.strategy.clone()
+        },
+        documents: &mut documents,
+        known_features: KnownFeatures::load(main_document.root_path())?,
+        checkpoint_path,
+        fingerprint,
+        verdicts,
+        is_plain: args.check,
     };
-    let to_be_disabled = pruner.run(features_to_test)?;
+    let mut jobs = pruner.run(features_to_test)?;
+    let Pruner {
+        checker, cache_dir, ..
+    } = pruner;
+
+    for job in jobs.iter().filter(|job| job.is_finished()) {
+        if let Some(key) = cache_keys.get(&(job.package_name.clone(), job.dependency_name.clone()))
+        {
+            cache.insert(key.clone(), job);
+        }
+    }
+
+    cache.save(&cache_path)?;
+
+    // every feature was checked on its own - the combination could still break the project
+    if args.no_verify.not() {
+        let conflicts = verify_removals(
+            &mut documents[0],
+            &checker,
+            worker_target_dir(cache_dir.as_ref(), 0).as_deref(),
+            &mut jobs,
+            &mut unused_dependencies,
+        )?;
+
+        if conflicts.is_empty().not() {
+            display_conflicts(&conflicts)?;
+        }
+    }
+
+    if let Some(path) = &args.report {
+        Report::new(
+            &main_document,
+            &jobs,
+            &kept_by_config,
+            &unused_dependencies,
+            &unified_features,
+        )?
+        .write(&args.report_format, path)?;
+    }
+
+    let mut removals = jobs
+        .iter()
+        .flat_map(|job| {
+            job.removable_features().into_iter().map(|feature| Removal {
+                package_name: job.package_name.clone(),
+                dependency_name: job.dependency_name.clone(),
+                feature,
+                decision: Decision::Remove,
+            })
+        })
+        .collect_vec();
+
+    if args.check {
+        return display_check_result(&main_document, &removals, &unused_dependencies);
+    }
 
     if args.dry_run {
-        return Ok(());
+        return Ok(true);
+    }
+
+    if args.review && removals.is_empty().not() && Review::new(&mut removals).start()?.not() {
+        println!("Review canceled - nothing was changed");
+        return Ok(true);
+    }
+
+    // ctrl-c could have been pressed after the last check
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        bail!("interrupted");
+    }
+
+    // the checks could have left the manifests or the Cargo.lock changed
+    if let Some(backup) = &backup {
+        backup.restore()?;
+    }
+
+    for unused in &unused_dependencies {
+        remove_dependency(
+            &main_document,
+            &unused.package_name,
+            &unused.dependency_name,
+        )?;
     }
 
-    for (package_name, dependency) in to_be_disabled {
-        for (dependency_name, features) in dependency {
-            for feature in features {
-                main_document
-                    .get_package_mut(&package_name)?
-                    .get_dep_mut(&dependency_name)?
-                    .disable_feature(&feature)?;
+    for (package_name, dependency_name) in removals
+        .iter()
+        .map(|removal| (&removal.package_name, &removal.dependency_name))
+        // the removals of a dependency are next to each other
+        .dedup()
+    {
+        let removals = removals.iter().filter(|removal| {
+            &removal.package_name == package_name && &removal.dependency_name == dependency_name
+        });
+
+        let dependency = main_document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?;
+
+        for removal in removals.clone() {
+            if removal.decision == Decision::Remove {
+                dependency.disable_feature(&removal.feature)?;
             }
+        }
 
-            save_dependency(&mut main_document, &package_name, &dependency_name)?;
+        // disabling a feature also disables the features depending on it - kept ones win
+        for removal in removals.clone() {
+            if removal.decision != Decision::Remove {
+                dependency.enable_feature(&removal.feature)?;
+            }
+        }
+
+        save_dependency(&mut main_document, package_name, dependency_name)?;
+
+        for removal in removals {
+            if removal.decision == Decision::KeepInConfig {
+                save_kept_feature(
+                    &main_document,
+                    package_name,
+                    dependency_name,
+                    &removal.feature,
+                )?;
+            }
         }
     }
 
-    Ok(())
+    if let Some(backup) = backup {
+        backup.keep();
+    }
+
+    Ok(true)
 }
 
-//give a map of known features that do not affect completion but remove functionality
-pub fn known_features() -> Result<HashMap<String, Vec<String>>> {
-    let file = include_str!("../../Known-Features.toml");
+/// lists everything which can be removed on stderr as e.g. `app/serde/std` - returns false if there is anything
+fn display_check_result(
+    document: &Document,
+    removals: &[Removal],
+    unused_dependencies: &[UnusedDependency],
+) -> Result<bool> {
+    let term = Term::stderr();
 
-    let document: toml_edit::DocumentMut = file.parse()?;
+    if removals.is_empty() && unused_dependencies.is_empty() {
+        return Ok(true);
+    }
 
-    let mut map = HashMap::new();
+    let mut lines = vec![];
 
-    for (dependency, features) in document.as_table() {
-        let features = features
-            .as_array()
-            .context("could not parse Known-Features.toml")?;
+    for removal in removals {
+        let path =
+            document.plain_dependency_path(&removal.package_name, &removal.dependency_name)?;
+        lines.push(format!("{path}/{}", removal.feature));
+    }
 
-        let features = features
-            .iter()
-            .filter_map(|item| item.as_str())
-            .map(|name| name.to_string())
-            .collect_vec();
+    for unused in unused_dependencies {
+        let path = document.plain_dependency_path(&unused.package_name, &unused.dependency_name)?;
+        lines.push(format!("{path} (unused)"));
+    }
 
-        map.insert(dependency.to_string(), features);
+    writeln!(
+        &term,
+        "found {} removable features and {} unused dependencies",
+        removals.len(),
+        unused_dependencies.len()
+    )?;
+
+    for line in lines.iter().sorted() {
+        writeln!(&term, "{line}")?;
     }
 
-    Ok(map)
+    Ok(false)
 }
 
-struct Pruner<'a> {
-    skip_tests: bool,
-    clean_level: CleanLevel,
-    document: &'a mut Document,
-    known_features: HashMap<String, Vec<String>>,
-}
+/// copies the project into `count` temporary directories
+///
+/// the temp dirs have to be kept alive as long as the documents are used
+pub fn create_copies(
+    main_document: &Document,
+    count: usize,
+) -> Result<(Vec<TempDir>, Vec<Document>)> {
+    let progress = ProgressBar::new_spinner().with_message("Creating temporary project...");
+    progress.enable_steady_tick(Duration::from_millis(100));
 
-impl<'a> Pruner<'a> {
-    fn run(&mut self, all_features: FeaturesMap) -> Result<FeaturesMap> {
-        let mut features_map = HashMap::new();
+    let root_path = main_document.root_path().canonicalize()?;
 
-        let mut has_known_features_enabled = false;
+    let mut tmp_dirs = vec![];
 
-        let mut display = Display::new(&all_features, self.document);
-        display.start()?;
+    for _ in 0..count {
+        let tmp_dir = TempDir::with_prefix_in(TMP_DIR_PREFIX, ".")?;
 
-        for (package_name, dependencies) in all_features
-            .into_iter()
-            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
-        {
-            if self.prune_package(
-                &package_name,
-                &dependencies,
-                &mut display,
-                &mut features_map,
-            )? {
-                has_known_features_enabled = true
-            };
+        copy_project(&root_path, tmp_dir.path())?;
+
+        tmp_dirs.push(tmp_dir);
+    }
+
+    progress.finish_and_clear();
+
+    let documents: Result<Vec<Document>> = tmp_dirs
+        .iter()
+        .map(|tmp_dir| Document::new(tmp_dir.path()))
+        .collect();
+
+    match documents {
+        Ok(documents) => Ok((tmp_dirs, documents)),
+        Err(err) => {
+            Err(err.wrap_err("Failed to create the temporary project - try to use `--no-tmp`"))
         }
+    }
+}
 
-        if has_known_features_enabled {
-            display.display_known_features_notice()?;
+/// copies the project without `target/` and `.git/` - the build output of the copies is kept in the cache dir instead
+///
+/// only the directories at the root of the project are excluded e.g. `src/target.rs` is still copied
+fn copy_project(root_path: &Path, destination: &Path) -> Result<()> {
+    for entry in fs::read_dir(root_path)? {
+        let entry = entry?;
+
+        if ["target", ".git"].contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
         }
 
-        display.finish()?;
+        copy_entry(&entry.path(), &destination.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
 
-        Ok(features_map)
+/// copies a file or a directory with all its content - skipping the other temporary copies
+fn copy_entry(source: &Path, destination: &Path) -> Result<()> {
+    if source
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(TMP_DIR_PREFIX))
+    {
+        return Ok(());
     }
 
-    fn prune_package(
-        &mut self,
-        package_name: &str,
-        dependencies: &HashMap<String, Vec<String>>,
-        display: &mut Display,
-        features_map: &mut FeaturesMap,
-    ) -> Result<bool> {
-        if dependencies.is_empty() {
-            return Ok(false);
+    if source.is_dir() {
+        fs::create_dir_all(destination)?;
+
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+
+            copy_entry(&entry.path(), &destination.join(entry.file_name()))?;
         }
+    } else {
+        fs::copy(source, destination)?;
+    }
 
-        let mut has_known_features_enabled = false;
+    Ok(())
+}
 
-        display.next_package(package_name, dependencies)?;
+/// disables the features of the dependency while `f` runs and enables the previously enabled ones afterwards
+pub fn with_features_disabled<T>(
+    document: &mut Document,
+    package_name: &PackageName,
+    dependency_name: &DependencyName,
+    features: &[FeatureName],
+    f: impl FnOnce(&Document) -> Result<T>,
+) -> Result<T> {
+    let enabled_features = document
+        .get_package(package_name)?
+        .get_dep(dependency_name)?
+        .get_enabled_features();
+
+    let dependency = document
+        .get_package_mut(package_name)?
+        .get_dep_mut(dependency_name)?;
+
+    for feature in features {
+        dependency.disable_feature(feature)?;
+    }
 
-        for (dependency_name, features) in dependencies
-            .iter()
-            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
-        {
-            if self.prune_dependency(
+    save_dependency(document, package_name, dependency_name)?;
+
+    let result = f(document);
+
+    //reset to start
+    let dependency = document
+        .get_package_mut(package_name)?
+        .get_dep_mut(dependency_name)?;
+
+    for feature in &enabled_features {
+        dependency.enable_feature(feature)?;
+    }
+
+    save_dependency(document, package_name, dependency_name)?;
+
+    result
+}
+
+fn worker_target_dir(cache_dir: Option<&PathBuf>, worker: usize) -> Option<PathBuf> {
+    cache_dir.map(|dir| dir.join(format!("worker-{worker}")))
+}
+
+/// the keys under which the verdicts of every dependency are cached
+fn cache_keys(
+    document: &Document,
+    features_to_test: &FeaturesMap,
+    checker: &Checker,
+) -> Result<HashMap<(PackageName, DependencyName), CacheKey>> {
+    let mut keys = HashMap::new();
+
+    let sources = Cache::sources(document)?;
+
+    for (package_name, dependencies) in features_to_test {
+        for (dependency_name, features) in dependencies {
+            let key = CacheKey::new(
+                document,
                 package_name,
                 dependency_name,
                 features,
-                display,
-                features_map,
-            )? {
-                has_known_features_enabled = true;
-            };
-        }
+                sources.clone(),
+                checker.description(),
+            )?;
 
-        if let CleanLevel::Package = self.clean_level {
-            clean(self.document.root_path())?;
+            keys.insert((package_name.clone(), dependency_name.clone()), key);
         }
-
-        Ok(has_known_features_enabled)
     }
 
-    fn prune_dependency(
-        &mut self,
-        package_name: &str,
-        dependency_name: &str,
-        features: &Vec<String>,
-        display: &mut Display,
-        features_map: &mut FeaturesMap,
-    ) -> Result<bool> {
-        if features.is_empty() {
-            return Ok(false);
-        }
+    Ok(keys)
+}
+
+/// dependencies which are not used by any of the targets can not be checked
+fn remove_foreign_dependencies(
+    document: &Document,
+    features_to_test: &mut FeaturesMap,
+    targets: &[Target],
+) -> Result<()> {
+    for (package_name, dependencies) in features_to_test.iter_mut() {
+        let package = document.get_package(package_name)?;
 
-        let mut has_known_features_enabled = false;
+        let mut foreign_dependencies = vec![];
 
-        let mut known_features_list = vec![];
-        let dependency = self
-            .document
-            .get_package(package_name)?
-            .get_dep(dependency_name)?;
+        for dependency_name in dependencies.keys() {
+            let dependency = package.get_dep(dependency_name)?;
 
-        for feature_name in self.known_features.get(dependency_name).unwrap_or(&vec![]) {
-            set_features_to_be_kept(
-                dependency,
-                feature_name.to_string(),
-                &mut known_features_list,
-            )
+            if targets.iter().all(|target| !target.applies_to(dependency)) {
+                foreign_dependencies.push(dependency_name.clone());
+            }
         }
 
-        let mut to_be_disabled = vec![];
-        to_be_disabled.append(&mut known_features_list.clone());
+        for dependency_name in foreign_dependencies {
+            dependencies.remove(&dependency_name);
+        }
+    }
+
+    features_to_test.retain(|_, dependencies| dependencies.is_empty().not());
+
+    Ok(())
+}
 
-        display.next_dependency(dependency_name, features);
+struct Pruner<'a> {
+    checker: Checker,
+    budget: Option<Duration>,
+    /// contains a persistent target directory for every worker so following runs start warm
+    cache_dir: Option<PathBuf>,
+    clean_level: CleanLevel,
+    strategy: PruneStrategy,
+    /// one project copy per worker
+    documents: &'a mut [Document],
+    known_features: KnownFeatures,
+    checkpoint_path: PathBuf,
+    fingerprint: String,
+    /// verdicts of a previous run which got interrupted
+    verdicts: Vec<Verdict>,
+    /// only print the results - without progress
+    is_plain: bool,
+}
+
+impl<'a> Pruner<'a> {
+    fn run(&mut self, all_features: FeaturesMap) -> Result<Vec<Job>> {
+        let mut display = Display::new(
+            &all_features,
+            &self.documents[0],
+            self.documents.len(),
+            self.is_plain,
+        );
+        display.start()?;
 
-        for (id, feature) in features.iter().enumerate() {
-            display.next_feature(id, feature)?;
+        // a budget beyond the range of `Instant` never runs out
+        let deadline = self
+            .budget
+            .and_then(|budget| Instant::now().checked_add(budget));
+        let scheduler =
+            SharedScheduler::new(Scheduler::new(self.create_jobs(&all_features)?, deadline));
+
+        let (sender, receiver) = mpsc::channel();
+
+        let checker = &self.checker;
+        let clean_level = self.clean_level.clone();
+        let cache_dir = self.cache_dir.as_ref();
+
+        thread::scope(|scope| -> Result<()> {
+            let workers = self
+                .documents
+                .iter_mut()
+                .enumerate()
+                .map(|(id, document)| {
+                    let worker = Worker {
+                        id,
+                        document,
+                        checker,
+                        clean_level: clean_level.clone(),
+                        target_dir: worker_target_dir(cache_dir, id),
+                    };
+
+                    let sender = sender.clone();
+                    let scheduler = &scheduler;
+
+                    scope.spawn(move || worker.run(scheduler, sender))
+                })
+                .collect_vec();
+
+            drop(sender);
+
+            let mut flushed_jobs = 0;
+            let mut current_package = None;
+
+            for event in receiver {
+                let scheduler = scheduler.lock();
+
+                match event {
+                    WorkerEvent::Started {
+                        worker,
+                        job,
+                        features,
+                    } => {
+                        display.next_feature(worker, &scheduler.jobs()[job], &features)?;
+                    }
+                    WorkerEvent::Finished { worker } => {
+                        display.finish_feature(worker, scheduler.jobs())?;
+
+                        Checkpoint::save(
+                            &self.checkpoint_path,
+                            &self.fingerprint,
+                            scheduler.jobs(),
+                        )?;
+                    }
+                }
 
-            self.document
-                .get_package_mut(package_name)?
-                .get_dep_mut(dependency_name)?
-                .disable_feature(feature)?;
+                flush_finished_jobs(
+                    &scheduler,
+                    &all_features,
+                    &mut display,
+                    &mut flushed_jobs,
+                    &mut current_package,
+                    false,
+                )?;
+            }
 
-            save_dependency(self.document, package_name, dependency_name)?;
+            let scheduler = scheduler.lock();
+            let is_interrupted = INTERRUPTED.load(Ordering::SeqCst);
 
-            if to_be_disabled.contains(feature).not()
-                && check(self.skip_tests, self.document.root_path())?
-            {
-                set_features_to_be_disabled(
-                    self.document
-                        .get_package(package_name)?
-                        .get_dep(dependency_name)?,
-                    feature.to_string(),
-                    &mut to_be_disabled,
-                );
+            // once the budget ran out or ctrl-c was pressed the unfinished jobs are shown with their partial results
+            flush_finished_jobs(
+                &scheduler,
+                &all_features,
+                &mut display,
+                &mut flushed_jobs,
+                &mut current_package,
+                scheduler.is_out_of_budget() || is_interrupted,
+            )?;
+
+            if is_interrupted {
+                display.display_interrupted_notice(
+                    scheduler
+                        .jobs()
+                        .iter()
+                        .map(|job| job.unchecked_features().len())
+                        .sum(),
+                )?;
             }
 
-            //reset to start
-            for feature in features {
-                self.document
-                    .get_package_mut(package_name)?
-                    .get_dep_mut(dependency_name)?
-                    .enable_feature(feature)?;
+            drop(scheduler);
+
+            for worker in workers {
+                worker
+                    .join()
+                    .map_err(|_| eyre!("a prune worker panicked"))??;
             }
 
-            save_dependency(self.document, package_name, dependency_name)?;
+            Ok(())
+        })?;
+
+        let scheduler = scheduler.into_inner();
 
-            display.finish_feature()?;
+        let unchecked_count = scheduler
+            .jobs()
+            .iter()
+            .map(|job| job.unchecked_features().len())
+            .sum::<usize>();
+
+        // the checkpoint allows to continue with the unchecked features later on
+        if unchecked_count == 0 {
+            Checkpoint::remove(&self.checkpoint_path)?;
+        } else {
+            display.display_budget_notice(unchecked_count)?;
         }
 
-        let features_result = features
+        let has_known_features_enabled = scheduler
+            .jobs()
             .iter()
-            .filter(|feature| to_be_disabled.contains(feature))
-            .map(|feature| {
-                if known_features_list.contains(feature) {
-                    has_known_features_enabled = true;
-                    (feature, true)
-                } else {
-                    (feature, false)
+            .any(|job| job.result().iter().any(|(_, is_known)| *is_known));
+
+        if has_known_features_enabled {
+            display
+                .display_known_features_notice(&self.known_feature_reasons(scheduler.jobs())?)?;
+        }
+
+        display.finish()?;
+
+        Ok(scheduler.into_jobs())
+    }
+
+    /// the reasons of the known features which were enabled e.g. "`hyper/http2`: enables HTTP/2"
+    ///
+    /// listed once even if multiple packages use the dependency
+    fn known_feature_reasons(&self, jobs: &[Job]) -> Result<Vec<String>> {
+        let mut reasons = BTreeSet::new();
+
+        for job in jobs {
+            let dependency = self.documents[0]
+                .get_package(&job.package_name)?
+                .get_dep(&job.dependency_name)?;
+
+            for known in self.known_features.of_dependency(dependency) {
+                if let Some(reason) = &known.reason
+                    && job.features.contains(&known.feature)
+                {
+                    reasons.insert(format!("`{}/{}`: {reason}", dependency.name, known.feature));
                 }
-            })
-            .collect();
+            }
+        }
+
+        Ok(reasons.into_iter().collect())
+    }
 
-        display.finish_dependency(features_result)?;
+    fn create_jobs(&self, all_features: &FeaturesMap) -> Result<Vec<Job>> {
+        let mut jobs = vec![];
 
-        if let CleanLevel::Dependency = self.clean_level {
-            clean(self.document.root_path())?;
+        for (package_name, dependencies) in all_features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                if features.is_empty() {
+                    continue;
+                }
+
+                let dependency = self.documents[0]
+                    .get_package(package_name)?
+                    .get_dep(dependency_name)?;
+
+                let mut known_features_list = vec![];
+
+                for known in self.known_features.of_dependency(dependency) {
+                    set_features_to_be_kept(
+                        dependency,
+                        known.feature.clone(),
+                        &mut known_features_list,
+                    )
+                }
+
+                jobs.push(Job::new(
+                    package_name.clone(),
+                    dependency_name.clone(),
+                    features.clone(),
+                    known_features_list,
+                    self.strategy.clone(),
+                    &self.verdicts,
+                ));
+            }
         }
 
-        let to_be_disabled = to_be_disabled
-            .into_iter()
-            .filter(|feature| known_features_list.contains(feature).not())
-            .collect_vec();
+        Ok(jobs)
+    }
+}
+
+/// displays the results of finished jobs in order - even if later jobs finished earlier
+fn flush_finished_jobs(
+    scheduler: &Scheduler,
+    all_features: &FeaturesMap,
+    display: &mut Display,
+    flushed_jobs: &mut usize,
+    current_package: &mut Option<PackageName>,
+    include_unfinished: bool,
+) -> Result<()> {
+    while let Some(job) = scheduler.jobs().get(*flushed_jobs) {
+        if job.has_started().not() && include_unfinished.not() {
+            break;
+        }
+
+        if current_package.as_ref() != Some(&job.package_name) {
+            display.next_package(&job.package_name, &all_features[&job.package_name])?;
+            *current_package = Some(job.package_name.clone());
+        }
+
+        if job.is_finished().not() && include_unfinished.not() {
+            break;
+        }
 
-        features_map
-            .entry(package_name.to_string())
-            .or_default()
-            .insert(dependency_name.to_string(), to_be_disabled);
+        display.finish_dependency(
+            &job.dependency_name,
+            job.features.len(),
+            job.result(),
+            job.kept_reasons(),
+        )?;
 
-        Ok(has_known_features_enabled)
+        *flushed_jobs += 1;
     }
+
+    Ok(())
 }
 
 fn set_features_to_be_disabled(
@@ -325,70 +883,41 @@ fn set_features_to_be_kept(
     }
 }
 
-fn clean<P: AsRef<Path>>(path: P) -> Result<()> {
-    let mut child = Command::new("cargo")
-        .current_dir(path)
-        .arg("clean")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    let _ = child.wait()?.code().ok_or(eyre!("Could not clear"))?;
-
-    Ok(())
-}
-
-fn check<P: AsRef<Path>>(skip_tests: bool, path: P) -> Result<bool> {
-    if !build(&path)? {
-        return Ok(false);
-    }
-
-    if !skip_tests && !test(&path)? {
-        return Ok(false);
-    }
-
-    Ok(true)
-}
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::fixture::project;
+    use crate::prune::copy_project;
+    use std::ops::Not;
+
+    #[test]
+    fn copies_leave_out_the_build_output_and_git() {
+        let dir = project(&[
+            ("Cargo.toml", "[package]"),
+            ("src/lib.rs", ""),
+            ("src/target.rs", ""),
+            ("src/target/mod.rs", ""),
+            ("target/debug/app", ""),
+            (".git/HEAD", ""),
+            (".cargo-features-manager-copy/Cargo.toml", ""),
+        ]);
+        let destination = tempfile::tempdir().unwrap();
+
+        copy_project(dir.path(), destination.path()).unwrap();
+
+        let copy = destination.path();
+
+        for path in [
+            "Cargo.toml",
+            "src/lib.rs",
+            "src/target.rs",
+            "src/target/mod.rs",
+        ] {
+            assert!(copy.join(path).is_file(), "{path} is missing");
+        }
 
-fn build<P: AsRef<Path>>(path: P) -> Result<bool> {
-    const BUILD_TARGETS: [&str; 6] = [
-        "--bins",
-        "--lib",
-        "--examples",
-        "--tests",
-        "--benches",
-        "--all-targets",
-    ];
-
-    for target in BUILD_TARGETS {
-        let mut child = Command::new("cargo")
-            .current_dir(&path)
-            .arg("build")
-            .arg(target)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
-
-        let code = child.wait()?.code().ok_or(eyre!("Could not build"))?;
-
-        if code != 0 {
-            return Ok(false);
+        for path in ["target", ".git", ".cargo-features-manager-copy"] {
+            assert!(copy.join(path).exists().not(), "{path} was copied");
         }
     }
-
-    Ok(true)
-}
-
-fn test<P: AsRef<Path>>(path: P) -> Result<bool> {
-    let mut child = Command::new("cargo")
-        .current_dir(path)
-        .arg("test")
-        .arg("--workspace")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    let code = child.wait()?.code().ok_or(eyre!("Could not test"))?;
-
-    Ok(code == 0)
 }
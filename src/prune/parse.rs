@@ -5,14 +5,17 @@ use crate::project::dependency::Dependency;
 use crate::project::document::Document;
 use crate::prune::FeaturesMap;
 use color_eyre::eyre::{ContextCompat, eyre};
+use itertools::Itertools;
 use std::collections::HashMap;
 use std::ops::Not;
 use std::path::Path;
 
+/// returns the features to test and the features which are kept because of `cargo-features-manager.keep`
 pub fn get_features_to_test(
     document: &Document,
     only_dependency_features: bool,
-) -> Result<FeaturesMap> {
+    only_default_features: bool,
+) -> Result<(FeaturesMap, FeaturesMap)> {
     let base_ignored_features =
         get_ignored_features("./", "workspace.cargo-features-manager.keep")?;
 
@@ -22,9 +25,14 @@ pub fn get_features_to_test(
         remove_non_dependency_features(document, &mut enabled_features)?;
     }
 
-    remove_ignored_features(document, &base_ignored_features, &mut enabled_features)?;
+    if only_default_features {
+        remove_non_default_features(document, &mut enabled_features)?;
+    }
+
+    let ignored_features =
+        remove_ignored_features(document, &base_ignored_features, &mut enabled_features)?;
 
-    Ok(enabled_features)
+    Ok((enabled_features, ignored_features))
 }
 
 fn get_enabled_features(document: &Document) -> FeaturesMap {
@@ -40,6 +48,7 @@ fn get_enabled_features(document: &Document) -> FeaturesMap {
                 .filter(|(_name, data)| data.is_toggleable() && data.is_enabled())
                 .map(|(name, _data)| name)
                 .cloned()
+                .sorted()
                 .collect::<Vec<String>>();
 
             if enabled_features.is_empty().not() {
@@ -121,11 +130,43 @@ fn remove_non_dependency_features(
     Ok(())
 }
 
+/// keeps only the features which get disabled by `default-features = false`
+fn remove_non_default_features(
+    document: &Document,
+    enabled_features: &mut FeaturesMap,
+) -> Result<()> {
+    for (package_name, dependencies) in enabled_features.iter_mut() {
+        let package = document.get_package(package_name)?;
+
+        for (dependency_name, features) in dependencies.iter_mut() {
+            let dependency = package.get_dep(dependency_name)?;
+
+            if dependency.can_use_default().not() {
+                features.clear();
+                continue;
+            }
+
+            let default_features = dependency.get_default_features();
+
+            features.retain(|feature| default_features.contains(feature));
+        }
+
+        dependencies.retain(|_, features| features.is_empty().not());
+    }
+
+    enabled_features.retain(|_, dependencies| dependencies.is_empty().not());
+
+    Ok(())
+}
+
+/// returns the removed features
 fn remove_ignored_features(
     document: &Document,
     base_ignored: &HashMap<String, Vec<String>>,
     enabled_features: &mut FeaturesMap,
-) -> Result<()> {
+) -> Result<FeaturesMap> {
+    let mut removed_features: FeaturesMap = HashMap::new();
+
     for (package_name, dependencies) in enabled_features {
         let package = document.get_package(package_name)?;
 
@@ -141,6 +182,8 @@ fn remove_ignored_features(
                 features.push("default".to_string());
             }
 
+            let all_features = features.clone();
+
             for feature in ignored_features.get(&dependency.name).unwrap_or(&vec![]) {
                 remove_feature(feature, features, dependency);
             }
@@ -148,16 +191,29 @@ fn remove_ignored_features(
                 remove_feature(feature, features, dependency);
             }
 
+            let removed = all_features
+                .into_iter()
+                .filter(|feature| features.contains(feature).not())
+                .collect::<Vec<String>>();
+
+            if removed.is_empty().not() {
+                removed_features
+                    .entry(package_name.clone())
+                    .or_default()
+                    .insert(dependency_name.clone(), removed);
+            }
+
             if let Some(index) = features.iter().position(|name| name == "default") {
                 features.remove(index);
             }
         }
     }
 
-    Ok(())
+    Ok(removed_features)
 }
 
-fn remove_feature(feature: &String, features: &mut Vec<String>, dependency: &Dependency) {
+/// removes the feature together with all features it enables
+pub fn remove_feature(feature: &String, features: &mut Vec<String>, dependency: &Dependency) {
     let index = features.iter().position(|name| name == feature);
 
     let Some(index) = index else {
@@ -172,3 +228,83 @@ fn remove_feature(feature: &String, features: &mut Vec<String>, dependency: &Dep
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::document::Document;
+    use crate::project::fixture::{app_with_dep, workspace_with_dep};
+    use crate::prune::parse::{get_enabled_features, remove_non_default_features};
+    use std::collections::HashMap;
+
+    fn workspace_name(document: &Document) -> String {
+        document
+            .get_workspace_package()
+            .unwrap()
+            .unwrap()
+            .name
+            .clone()
+    }
+
+    #[test]
+    fn inherited_features_are_tested_as_part_of_the_workspace() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", features = ["b"] }"#,
+            r#"{ workspace = true }"#,
+        );
+        let document = Document::new(dir.path()).unwrap();
+
+        assert_eq!(
+            get_enabled_features(&document),
+            HashMap::from([(
+                workspace_name(&document),
+                HashMap::from([("dep".to_string(), vec!["a".to_string(), "b".to_string()])])
+            )])
+        );
+    }
+
+    #[test]
+    fn features_of_a_member_are_tested_as_part_of_the_member() {
+        let dir = workspace_with_dep(
+            r#"{ path = "dep", default-features = false }"#,
+            r#"{ workspace = true, features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path()).unwrap();
+
+        let dependency_name = document.get_package("app").unwrap().get_deps()[0].get_name();
+
+        assert_eq!(
+            get_enabled_features(&document),
+            HashMap::from([(
+                "app".to_string(),
+                HashMap::from([(dependency_name, vec!["b".to_string()])])
+            )])
+        );
+    }
+
+    #[test]
+    fn only_default_features_are_kept() {
+        let dir = app_with_dep(
+            r#"[dependencies]
+dep = { path = "../dep", features = ["b"] }
+
+[dev-dependencies]
+dep = { path = "../dep", default-features = false, features = ["b"] }"#,
+        );
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let mut enabled_features = get_enabled_features(&document);
+        assert_eq!(enabled_features["app"].len(), 2);
+
+        remove_non_default_features(&document, &mut enabled_features).unwrap();
+
+        // the dev-dependency does not use its default features already
+        assert_eq!(
+            enabled_features,
+            HashMap::from([(
+                "app".to_string(),
+                HashMap::from([("dep".to_string(), vec!["a".to_string()])])
+            )])
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use crate::project::document::Document;
+use crate::prune::check::CheckOutcome;
+use crate::prune::filter::DependencyKind;
+use crate::prune::scheduler::Job;
+use crate::prune::unification::{Dependents, UnifiedFeatures};
+use crate::prune::unused::UnusedDependency;
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::ops::Not;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Markdown,
+}
+
+/// the packages are keyed by their plain name - `[workspace]` for `[workspace.dependencies]`
+#[derive(Serialize)]
+pub struct Report {
+    packages: BTreeMap<PackageName, Vec<DependencyReport>>,
+    /// dependencies the project works without
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    unused_dependencies: BTreeMap<PackageName, Vec<DependencyId>>,
+    /// features which are not tested as other crates enable them as well
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    unified_features: BTreeMap<PackageName, Vec<UnifiedReport>>,
+}
+
+/// a dependency without the styling of the terminal which depends on the locale
+///
+/// the same crate can be e.g. a normal and a dev-dependency of a package
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DependencyId {
+    name: DependencyName,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<DependencyKind>,
+    /// e.g. `cfg(windows)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    /// inherited with `workspace = true`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    workspace: bool,
+}
+
+impl DependencyId {
+    /// returns the plain name of the package as well
+    pub fn new(
+        document: &Document,
+        package_name: &str,
+        dependency_name: &str,
+    ) -> Result<(PackageName, Self)> {
+        let dependency = document
+            .get_package(package_name)?
+            .get_dep(dependency_name)?;
+
+        Ok((
+            document.plain_package_name(package_name)?,
+            Self {
+                name: dependency.name.clone(),
+                kind: DependencyKind::of(dependency),
+                target: dependency.target.as_ref().map(|target| target.to_string()),
+                workspace: dependency.workspace,
+            },
+        ))
+    }
+}
+
+#[derive(Serialize, Default)]
+struct DependencyReport {
+    #[serde(flatten)]
+    dependency: DependencyId,
+    tested: Vec<FeatureName>,
+    removable: Vec<FeatureName>,
+    /// required to pass the checks
+    kept: Vec<FeatureName>,
+    /// the first error of the check which proved a kept feature to be required
+    kept_reasons: BTreeMap<FeatureName, String>,
+    /// not checked because the budget ran out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unchecked: Vec<FeatureName>,
+    /// enabled and listed as known feature
+    kept_known: Vec<FeatureName>,
+    /// listed in `cargo-features-manager.keep`
+    kept_by_config: Vec<FeatureName>,
+    checks: Vec<CheckReport>,
+}
+
+#[derive(Serialize)]
+struct UnifiedReport {
+    #[serde(flatten)]
+    dependency: DependencyId,
+    features: BTreeMap<FeatureName, Dependents>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CheckReport {
+    pub features: Vec<FeatureName>,
+    pub is_removable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub duration_secs: f64,
+}
+
+impl CheckReport {
+    pub fn new(features: Vec<FeatureName>, outcome: &CheckOutcome, duration: Duration) -> Self {
+        Self {
+            features,
+            is_removable: outcome.is_success(),
+            reason: outcome.reason().cloned(),
+            duration_secs: duration.as_secs_f64(),
+        }
+    }
+}
+
+impl Report {
+    pub fn new(
+        document: &Document,
+        jobs: &[Job],
+        kept_by_config: &FeaturesMap,
+        unused_dependencies: &[UnusedDependency],
+        unified_features: &UnifiedFeatures,
+    ) -> Result<Self> {
+        let mut reports = BTreeMap::new();
+
+        for (package_name, dependencies) in kept_by_config {
+            for (dependency_name, features) in dependencies {
+                report_of(&mut reports, document, package_name, dependency_name)?.kept_by_config =
+                    features.iter().sorted().cloned().collect();
+            }
+        }
+
+        for job in jobs {
+            let dependency = document
+                .get_package(&job.package_name)?
+                .get_dep(&job.dependency_name)?;
+
+            let report = report_of(
+                &mut reports,
+                document,
+                &job.package_name,
+                &job.dependency_name,
+            )?;
+
+            report.tested = job.features.iter().sorted().cloned().collect();
+            report.removable = job.removable_features().into_iter().sorted().collect();
+            report.kept = job.kept_features().into_iter().sorted().collect();
+            report.kept_reasons = job
+                .kept_reasons()
+                .into_iter()
+                .map(|(feature, reason)| (feature.clone(), reason.clone()))
+                .collect();
+            report.unchecked = job.unchecked_features().into_iter().sorted().collect();
+            // the known features also contain the features they enable - even if those are disabled
+            report.kept_known = job
+                .known_features
+                .iter()
+                .filter(|feature| {
+                    dependency
+                        .features
+                        .get(*feature)
+                        .is_some_and(|data| data.is_enabled())
+                })
+                .sorted()
+                .cloned()
+                .collect();
+            report.checks = job.checks.clone();
+        }
+
+        let mut unused_dependency_ids: BTreeMap<PackageName, Vec<DependencyId>> = BTreeMap::new();
+
+        for unused in unused_dependencies {
+            let (package_name, id) =
+                DependencyId::new(document, &unused.package_name, &unused.dependency_name)?;
+
+            unused_dependency_ids
+                .entry(package_name)
+                .or_default()
+                .push(id);
+        }
+
+        for ids in unused_dependency_ids.values_mut() {
+            ids.sort();
+        }
+
+        let mut unified_feature_reports: BTreeMap<PackageName, Vec<UnifiedReport>> =
+            BTreeMap::new();
+
+        for (package_name, dependency_name, features) in unified_features.iter() {
+            let (package_name, id) = DependencyId::new(document, package_name, dependency_name)?;
+
+            unified_feature_reports
+                .entry(package_name)
+                .or_default()
+                .push(UnifiedReport {
+                    dependency: id,
+                    features: features.clone(),
+                });
+        }
+
+        for reports in unified_feature_reports.values_mut() {
+            reports.sort_by(|a, b| a.dependency.cmp(&b.dependency));
+        }
+
+        Ok(Self {
+            packages: reports
+                .into_iter()
+                .map(|(package_name, reports)| (package_name, reports.into_values().collect()))
+                .collect(),
+            unused_dependencies: unused_dependency_ids,
+            unified_features: unified_feature_reports,
+        })
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, format: &ReportFormat, path: P) -> Result<()> {
+        let content = match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ReportFormat::Markdown => self.to_markdown()?,
+        };
+
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    fn to_markdown(&self) -> Result<String> {
+        let mut markdown = String::new();
+
+        writeln!(markdown, "# Prune report")?;
+
+        if self.unused_dependencies.is_empty().not() {
+            writeln!(markdown)?;
+            writeln!(markdown, "## Unused dependencies")?;
+            writeln!(markdown)?;
+
+            for (package_name, dependencies) in &self.unused_dependencies {
+                writeln!(
+                    markdown,
+                    "- {}: {}",
+                    package_name,
+                    dependencies.iter().map(markdown_dependency).join(", ")
+                )?;
+            }
+        }
+
+        if self.unified_features.is_empty().not() {
+            writeln!(markdown)?;
+            writeln!(markdown, "## Features enabled by other dependencies")?;
+            writeln!(markdown)?;
+
+            for (package_name, reports) in &self.unified_features {
+                for report in reports {
+                    for (feature, dependents) in &report.features {
+                        writeln!(
+                            markdown,
+                            "- {}: `{}/{}` by {}",
+                            package_name,
+                            report.dependency.name,
+                            feature,
+                            dependents.iter().join(", ")
+                        )?;
+                    }
+                }
+            }
+        }
+
+        for (package_name, dependencies) in &self.packages {
+            writeln!(markdown)?;
+            writeln!(markdown, "## {}", package_name)?;
+            writeln!(markdown)?;
+            writeln!(
+                markdown,
+                "| dependency | tested | removable | kept | unchecked | known features | kept by config | checks | duration |"
+            )?;
+            writeln!(markdown, "|---|---|---|---|---|---|---|---|---|")?;
+
+            for report in dependencies {
+                // an empty `sum` of floats is -0.0
+                let duration = report
+                    .checks
+                    .iter()
+                    .fold(0.0, |duration, check| duration + check.duration_secs);
+
+                writeln!(
+                    markdown,
+                    "| {} | {} | {} | {} | {} | {} | {} | {} | {:.1}s |",
+                    markdown_dependency(&report.dependency),
+                    markdown_list(&report.tested),
+                    markdown_list(&report.removable),
+                    markdown_kept_list(report),
+                    markdown_list(&report.unchecked),
+                    markdown_list(&report.kept_known),
+                    markdown_list(&report.kept_by_config),
+                    report.checks.len(),
+                    duration
+                )?;
+            }
+        }
+
+        Ok(markdown)
+    }
+}
+
+/// the report of the dependency - created if it does not exist yet
+fn report_of<'a>(
+    reports: &'a mut BTreeMap<PackageName, BTreeMap<DependencyId, DependencyReport>>,
+    document: &Document,
+    package_name: &str,
+    dependency_name: &str,
+) -> Result<&'a mut DependencyReport> {
+    let (package_name, id) = DependencyId::new(document, package_name, dependency_name)?;
+
+    Ok(reports
+        .entry(package_name)
+        .or_default()
+        .entry(id.clone())
+        .or_insert_with(|| DependencyReport {
+            dependency: id,
+            ..Default::default()
+        }))
+}
+
+fn markdown_list(features: &[FeatureName]) -> String {
+    features
+        .iter()
+        .map(|feature| format!("`{feature}`"))
+        .join(", ")
+}
+
+/// kept features followed by the reason they are required
+fn markdown_kept_list(report: &DependencyReport) -> String {
+    report
+        .kept
+        .iter()
+        .map(|feature| match report.kept_reasons.get(feature) {
+            Some(reason) => format!("`{feature}` ({})", reason.replace('|', "\\|")),
+            None => format!("`{feature}`"),
+        })
+        .join(", ")
+}
+
+/// e.g. `serde` (dev, cfg(unix), workspace)
+fn markdown_dependency(dependency: &DependencyId) -> String {
+    let details = [
+        match dependency.kind {
+            Some(DependencyKind::Normal) => None,
+            Some(DependencyKind::Dev) => Some("dev".to_string()),
+            Some(DependencyKind::Build) => Some("build".to_string()),
+            None => Some("unknown".to_string()),
+        },
+        dependency.target.clone(),
+        dependency.workspace.then(|| "workspace".to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect_vec();
+
+    if details.is_empty() {
+        format!("`{}`", dependency.name)
+    } else {
+        format!("`{}` ({})", dependency.name, details.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::prune::filter::DependencyKind;
+    use crate::prune::report::{DependencyId, DependencyReport, Report};
+    use std::collections::BTreeMap;
+
+    fn report() -> Report {
+        let serde = DependencyId {
+            name: "serde".to_string(),
+            kind: Some(DependencyKind::Normal),
+            target: None,
+            workspace: true,
+        };
+
+        let tokio = DependencyId {
+            name: "tokio".to_string(),
+            kind: Some(DependencyKind::Dev),
+            target: Some("cfg(unix)".to_string()),
+            workspace: false,
+        };
+
+        Report {
+            packages: BTreeMap::from([(
+                "app".to_string(),
+                vec![
+                    DependencyReport {
+                        dependency: serde,
+                        tested: vec!["derive".to_string(), "std".to_string()],
+                        removable: vec!["std".to_string()],
+                        kept: vec!["derive".to_string()],
+                        kept_reasons: BTreeMap::from([(
+                            "derive".to_string(),
+                            "error[E0433] in src/lib.rs:1 | 2".to_string(),
+                        )]),
+                        ..Default::default()
+                    },
+                    DependencyReport {
+                        dependency: tokio.clone(),
+                        tested: vec!["full".to_string()],
+                        unchecked: vec!["full".to_string()],
+                        kept_known: vec!["rt".to_string()],
+                        ..Default::default()
+                    },
+                ],
+            )]),
+            unused_dependencies: BTreeMap::from([("[workspace]".to_string(), vec![tokio])]),
+            unified_features: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn json_uses_plain_names() {
+        let json = serde_json::to_value(report()).unwrap();
+
+        let serde = &json["packages"]["app"][0];
+        assert_eq!(serde["name"], "serde");
+        assert_eq!(serde["kind"], "normal");
+        assert_eq!(serde["workspace"], true);
+        assert!(serde.get("target").is_none());
+        assert_eq!(serde["removable"], serde_json::json!(["std"]));
+
+        let tokio = &json["packages"]["app"][1];
+        assert_eq!(tokio["kind"], "dev");
+        assert_eq!(tokio["target"], "cfg(unix)");
+        assert!(tokio.get("workspace").is_none());
+
+        assert_eq!(
+            json["unused_dependencies"]["[workspace]"][0]["name"],
+            "tokio"
+        );
+        assert!(json.get("unified_features").is_none());
+    }
+
+    #[test]
+    fn markdown_lists_every_dependency() {
+        let markdown = report().to_markdown().unwrap();
+
+        assert_eq!(
+            markdown,
+            "# Prune report
+
+## Unused dependencies
+
+- [workspace]: `tokio` (dev, cfg(unix))
+
+## app
+
+| dependency | tested | removable | kept | unchecked | known features | kept by config | checks | duration |
+|---|---|---|---|---|---|---|---|---|
+| `serde` (workspace) | `derive`, `std` | `std` | `derive` (error[E0433] in src/lib.rs:1 \\| 2) |  |  |  | 0 | 0.0s |
+| `tokio` (dev, cfg(unix)) | `full` |  |  | `full` | `rt` |  | 0 | 0.0s |
+"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::edit::display::get_visible_range;
+use crate::edit::filter_view::FilterView;
+use crate::edit::filter_view::item::FilterViewItem;
+use crate::prune::{DependencyName, FeatureName, PackageName};
+use color_eyre::Result;
+use console::{Key, Term, style};
+use std::io::Write;
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Decision {
+    Remove,
+    Keep,
+    /// keep and add it to `cargo-features-manager.keep`
+    KeepInConfig,
+}
+
+impl Decision {
+    fn next(self) -> Self {
+        match self {
+            Decision::Remove => Decision::Keep,
+            Decision::Keep => Decision::KeepInConfig,
+            Decision::KeepInConfig => Decision::Remove,
+        }
+    }
+}
+
+/// a feature prune found to be removable
+pub struct Removal {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    pub feature: FeatureName,
+    pub decision: Decision,
+}
+
+/// lets the user decide which of the removable features are really removed
+pub struct Review<'a> {
+    term: Term,
+    selector: FilterView,
+    removals: &'a mut [Removal],
+}
+
+impl<'a> Review<'a> {
+    pub fn new(removals: &'a mut [Removal]) -> Self {
+        let show_package = removals
+            .iter()
+            .any(|removal| removal.package_name != removals[0].package_name);
+
+        Self {
+            term: Term::buffered_stdout(),
+            selector: FilterView {
+                selected_index: 0,
+                data: removals
+                    .iter()
+                    .map(|removal| {
+                        FilterViewItem::from_removal(
+                            &removal.package_name,
+                            &removal.dependency_name,
+                            &removal.feature,
+                            show_package,
+                        )
+                    })
+                    .collect(),
+            },
+            removals,
+        }
+    }
+
+    /// returns false if the review was canceled
+    pub fn start(&mut self) -> Result<bool> {
+        self.term.hide_cursor()?;
+
+        for _ in 1..self.term.size().0 {
+            writeln!(self.term)?;
+        }
+
+        self.term.move_cursor_to(0, 0)?;
+        self.term.flush()?;
+
+        let result = loop {
+            self.display()?;
+
+            self.term.flush()?;
+
+            //clear previous screen
+            self.term.clear_last_lines(self.term.size().0 as usize)?;
+
+            match self.input_event()? {
+                ReviewState::Running => {}
+                ReviewState::Applied => break true,
+                ReviewState::Canceled => break false,
+            }
+        };
+
+        self.term.show_cursor()?;
+        self.term.flush()?;
+
+        Ok(result)
+    }
+
+    fn display(&mut self) -> Result<()> {
+        write!(
+            self.term,
+            "Removable features {}",
+            style("- space: remove / keep / keep in config, enter: apply, esc: cancel").color256(8)
+        )?;
+
+        let range = self.get_max_range();
+
+        for (line_index, (index, item)) in
+            (1..).zip((range.start..).zip(&self.selector.data[range]))
+        {
+            if index == self.selector.selected_index {
+                self.term.move_cursor_to(0, line_index)?;
+                write!(self.term, ">")?;
+            }
+
+            self.term.move_cursor_to(2, line_index)?;
+
+            let marker = match self.removals[index].decision {
+                Decision::Remove => style("[X]").red(),
+                Decision::Keep => style("[ ]"),
+                Decision::KeepInConfig => style("[K]").green(),
+            };
+
+            write!(self.term, "{} {}", marker, item.display_name())?;
+        }
+
+        Ok(())
+    }
+
+    fn input_event(&mut self) -> Result<ReviewState> {
+        let key = match self.term.read_key() {
+            Ok(key) => key,
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    return Ok(ReviewState::Canceled);
+                }
+
+                return Err(err.into());
+            }
+        };
+
+        match key {
+            Key::ArrowUp => self.selector.shift(-1),
+            Key::ArrowDown => self.selector.shift(1),
+            Key::Char(' ') | Key::ArrowRight if self.selector.has_data() => {
+                let removal = &mut self.removals[self.selector.selected_index];
+                removal.decision = removal.decision.next();
+            }
+            Key::Enter => return Ok(ReviewState::Applied),
+            Key::Escape | Key::Char('q') => return Ok(ReviewState::Canceled),
+            _ => {}
+        }
+
+        Ok(ReviewState::Running)
+    }
+
+    fn get_max_range(&self) -> Range<usize> {
+        get_visible_range(
+            self.selector.selected_index,
+            self.selector.data.len(),
+            self.term.size().0 as usize,
+            0,
+        )
+    }
+}
+
+enum ReviewState {
+    Running,
+    Applied,
+    Canceled,
+}
@@ -0,0 +1,431 @@
+use crate::PruneStrategy;
+use crate::project::dependency::Dependency;
+use crate::prune::check::CheckOutcome;
+use crate::prune::checkpoint::Verdict;
+use crate::prune::report::CheckReport;
+use crate::prune::strategy::{Search, Test};
+use crate::prune::{DependencyName, FeatureName, PackageName, set_features_to_be_disabled};
+use std::collections::HashMap;
+use std::ops::Not;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+pub struct Job {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    pub features: Vec<FeatureName>,
+    /// features that are kept because they are listed in Known-Features.toml
+    pub known_features: Vec<FeatureName>,
+    pub to_be_disabled: Vec<FeatureName>,
+    pub checks: Vec<CheckReport>,
+
+    search: Search,
+    /// the first error of the check which failed for a single feature
+    failure_reasons: HashMap<FeatureName, String>,
+    /// removable on their own but not together with the removals of the other jobs
+    conflicting: Vec<FeatureName>,
+}
+
+impl Job {
+    pub fn new(
+        package_name: PackageName,
+        dependency_name: DependencyName,
+        features: Vec<FeatureName>,
+        known_features: Vec<FeatureName>,
+        strategy: PruneStrategy,
+        verdicts: &[Verdict],
+    ) -> Self {
+        let mut removed = vec![];
+        let mut kept = vec![];
+        let mut failure_reasons = HashMap::new();
+
+        // results of a previous run
+        for verdict in verdicts {
+            if verdict.is_for(&package_name, &dependency_name).not() {
+                continue;
+            }
+
+            if verdict.is_removable {
+                removed.push(verdict.feature.clone());
+            } else {
+                kept.push(verdict.feature.clone());
+            }
+
+            if let Some(reason) = &verdict.reason {
+                failure_reasons.insert(verdict.feature.clone(), reason.clone());
+            }
+        }
+
+        // the known features are kept - ddmin must not disable them in the base of its tests
+        let to_be_disabled = known_features.iter().chain(&removed).cloned().collect();
+
+        Self {
+            package_name,
+            dependency_name,
+            search: Search::new(strategy, &features, removed, kept),
+            features,
+            to_be_disabled,
+            known_features,
+            checks: vec![],
+            failure_reasons,
+            conflicting: vec![],
+        }
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.search.has_started() || self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.search.is_finished()
+    }
+
+    /// the amount of features for which it is already known if they can be disabled
+    pub fn checked(&self) -> usize {
+        self.features
+            .iter()
+            .filter(|feature| {
+                self.to_be_disabled.contains(feature) || self.search.kept().contains(feature)
+            })
+            .count()
+    }
+
+    /// the features which can be disabled in the order they were given
+    pub fn result(&self) -> Vec<(&FeatureName, bool)> {
+        self.features
+            .iter()
+            .filter(|feature| self.to_be_disabled.contains(feature))
+            .filter(|feature| self.conflicting.contains(feature).not())
+            .map(|feature| (feature, self.known_features.contains(feature)))
+            .collect()
+    }
+
+    /// features which are required - excluding known features
+    pub fn kept_features(&self) -> Vec<FeatureName> {
+        self.search
+            .kept()
+            .iter()
+            .chain(&self.conflicting)
+            .cloned()
+            .collect()
+    }
+
+    /// keeps a removable feature which breaks the project together with the other removals
+    pub fn keep_conflicting(&mut self, feature: FeatureName, reason: String) {
+        self.failure_reasons.insert(feature.clone(), reason);
+        self.conflicting.push(feature);
+    }
+
+    /// features which were not checked e.g. because the budget ran out
+    pub fn unchecked_features(&self) -> Vec<FeatureName> {
+        self.features
+            .iter()
+            .filter(|feature| {
+                self.to_be_disabled.contains(feature).not()
+                    && self.search.kept().contains(feature).not()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// why a kept feature is required - if it was the only feature of a failed check
+    pub fn failure_reason(&self, feature: &FeatureName) -> Option<&String> {
+        self.failure_reasons.get(feature)
+    }
+
+    /// the kept features with their failure reason in the order they were given
+    pub fn kept_reasons(&self) -> Vec<(&FeatureName, &String)> {
+        self.features
+            .iter()
+            .filter(|feature| self.kept_features().contains(*feature))
+            .filter_map(|feature| Some((feature, self.failure_reason(feature)?)))
+            .collect()
+    }
+
+    /// the tested features which can be disabled - features which depend on them are disabled implicitly
+    pub fn removable_features(&self) -> Vec<FeatureName> {
+        self.features
+            .iter()
+            .filter(|feature| self.to_be_disabled.contains(feature))
+            .filter(|feature| self.known_features.contains(feature).not())
+            .filter(|feature| self.conflicting.contains(feature).not())
+            .cloned()
+            .collect()
+    }
+}
+
+pub struct Task {
+    pub job: usize,
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    pub test: Test,
+}
+
+/// hands out the tests of all jobs to the workers in order
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    is_aborted: bool,
+    /// no new tasks are handed out after the deadline
+    deadline: Option<Instant>,
+}
+
+impl Scheduler {
+    pub fn new(jobs: Vec<Job>, deadline: Option<Instant>) -> Self {
+        Self {
+            jobs,
+            is_aborted: false,
+            deadline,
+        }
+    }
+
+    pub fn jobs(&self) -> &Vec<Job> {
+        &self.jobs
+    }
+
+    pub fn into_jobs(self) -> Vec<Job> {
+        self.jobs
+    }
+
+    pub fn next_task(&mut self) -> Option<Task> {
+        if self.is_aborted || self.is_out_of_budget() {
+            return None;
+        }
+
+        self.jobs.iter_mut().enumerate().find_map(|(index, job)| {
+            let test = job.search.next_test(&job.to_be_disabled)?;
+
+            Some(Task {
+                job: index,
+                package_name: job.package_name.clone(),
+                dependency_name: job.dependency_name.clone(),
+                test,
+            })
+        })
+    }
+
+    /// returns true if all jobs are finished or no more tasks will be handed out
+    pub fn is_finished(&self) -> bool {
+        self.is_aborted || self.is_out_of_budget() || self.jobs.iter().all(|job| job.is_finished())
+    }
+
+    pub fn is_out_of_budget(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn finish_task(
+        &mut self,
+        task: &Task,
+        outcome: &CheckOutcome,
+        duration: Duration,
+        dependency: &Dependency,
+    ) {
+        let job = &mut self.jobs[task.job];
+        let is_removable = outcome.is_success();
+
+        job.search.finish_test(&task.test, is_removable);
+        job.checks.push(CheckReport::new(
+            task.test.features.clone(),
+            outcome,
+            duration,
+        ));
+
+        if let (Some(reason), [feature]) = (outcome.reason(), task.test.features.as_slice()) {
+            job.failure_reasons.insert(feature.clone(), reason.clone());
+        }
+
+        if is_removable {
+            for feature in &task.test.features {
+                set_features_to_be_disabled(dependency, feature.clone(), &mut job.to_be_disabled);
+            }
+        }
+    }
+
+    /// stop handing out new tasks e.g. after a worker failed
+    pub fn abort(&mut self) {
+        self.is_aborted = true;
+    }
+}
+
+/// the scheduler shared between all workers
+pub struct SharedScheduler {
+    scheduler: Mutex<Scheduler>,
+    changed: Condvar,
+}
+
+impl SharedScheduler {
+    pub fn new(scheduler: Scheduler) -> Self {
+        Self {
+            scheduler: Mutex::new(scheduler),
+            changed: Condvar::new(),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Scheduler> {
+        self.scheduler.lock().expect("scheduler poisoned")
+    }
+
+    /// waits until a task is available - returns none once all jobs are finished
+    pub fn wait_for_task(&self) -> Option<Task> {
+        let mut scheduler = self.lock();
+
+        loop {
+            if let Some(task) = scheduler.next_task() {
+                return Some(task);
+            }
+
+            if scheduler.is_finished() {
+                return None;
+            }
+
+            scheduler = self.changed.wait(scheduler).expect("scheduler poisoned");
+        }
+    }
+
+    pub fn finish_task(
+        &self,
+        task: &Task,
+        outcome: &CheckOutcome,
+        duration: Duration,
+        dependency: &Dependency,
+    ) {
+        self.lock().finish_task(task, outcome, duration, dependency);
+        self.changed.notify_all();
+    }
+
+    pub fn abort(&self) {
+        self.lock().abort();
+        self.changed.notify_all();
+    }
+
+    pub fn into_inner(self) -> Scheduler {
+        self.scheduler.into_inner().expect("scheduler poisoned")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::PruneStrategy;
+    use crate::project::dependency::feature::{
+        EnabledState, FeatureData, SubFeature, SubFeatureType,
+    };
+    use crate::project::dependency::{Dependency, DependencyType};
+    use crate::prune::check::CheckOutcome;
+    use crate::prune::scheduler::{Job, Scheduler};
+    use std::collections::HashMap;
+    use std::ops::Not;
+    use std::time::Duration;
+
+    fn feature(sub_features: &[&str]) -> FeatureData {
+        FeatureData {
+            sub_features: sub_features
+                .iter()
+                .map(|name| SubFeature {
+                    name: name.to_string(),
+                    kind: SubFeatureType::Normal,
+                })
+                .collect(),
+            is_default: false,
+            enabled_state: EnabledState::Normal(true),
+        }
+    }
+
+    #[test]
+    fn implied_features_are_not_removable() {
+        let dependency = Dependency {
+            name: "dep".to_string(),
+            rename: None,
+            comment: None,
+            version: "1".to_string(),
+            resolved_version: None,
+            workspace: false,
+            kind: DependencyType::Normal,
+            target: None,
+            features: HashMap::from([
+                ("default".to_string(), feature(&["std"])),
+                ("std".to_string(), feature(&[])),
+                ("alloc".to_string(), feature(&[])),
+            ]),
+        };
+
+        let job = Job::new(
+            "package".to_string(),
+            "dep".to_string(),
+            vec!["alloc".to_string(), "std".to_string()],
+            vec![],
+            PruneStrategy::Linear,
+            &[],
+        );
+
+        let mut scheduler = Scheduler::new(vec![job], None);
+
+        while let Some(task) = scheduler.next_task() {
+            let outcome = if task.test.features == ["alloc"] {
+                CheckOutcome::Failure("error[E0433] in src/lib.rs:1".to_string())
+            } else {
+                CheckOutcome::Success
+            };
+
+            scheduler.finish_task(&task, &outcome, Duration::ZERO, &dependency);
+        }
+
+        let job = &scheduler.jobs()[0];
+
+        assert!(job.is_finished());
+        // `default` is disabled together with `std` but was never tested
+        assert!(job.to_be_disabled.contains(&"default".to_string()));
+        assert_eq!(job.removable_features(), ["std"]);
+        assert_eq!(job.kept_features(), ["alloc"]);
+    }
+
+    #[test]
+    fn ddmin_keeps_known_features_enabled() {
+        let dependency = Dependency {
+            name: "dep".to_string(),
+            rename: None,
+            comment: None,
+            version: "1".to_string(),
+            resolved_version: None,
+            workspace: false,
+            kind: DependencyType::Normal,
+            target: None,
+            features: HashMap::from([
+                ("a".to_string(), feature(&[])),
+                ("b".to_string(), feature(&[])),
+                ("known".to_string(), feature(&[])),
+            ]),
+        };
+
+        let job = Job::new(
+            "package".to_string(),
+            "dep".to_string(),
+            vec!["a".to_string(), "b".to_string()],
+            vec!["known".to_string()],
+            PruneStrategy::Ddmin,
+            &[],
+        );
+
+        let mut scheduler = Scheduler::new(vec![job], None);
+
+        while let Some(task) = scheduler.next_task() {
+            assert!(task.test.base.contains(&"known".to_string()).not());
+
+            // `a` and `b` are only removable on their own
+            let disabled = task.test.features.iter().chain(&task.test.base).count();
+            let outcome = if disabled == 1 {
+                CheckOutcome::Success
+            } else {
+                CheckOutcome::Failure("error[E0433] in src/lib.rs:1".to_string())
+            };
+
+            scheduler.finish_task(&task, &outcome, Duration::ZERO, &dependency);
+        }
+
+        let job = &scheduler.jobs()[0];
+
+        assert!(job.is_finished());
+        assert_eq!(job.removable_features().len(), 1);
+        assert_eq!(job.kept_features().len(), 1);
+    }
+}
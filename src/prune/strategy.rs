@@ -0,0 +1,283 @@
+use crate::PruneStrategy;
+use crate::prune::FeatureName;
+use itertools::Itertools;
+use std::collections::VecDeque;
+use std::ops::Not;
+
+/// a set of features which get disabled together
+#[derive(Clone, Debug)]
+pub struct Test {
+    pub features: Vec<FeatureName>,
+    /// features which are already known to be removable and stay disabled during the test
+    pub base: Vec<FeatureName>,
+}
+
+/// decides which features of a single dependency get tested next
+pub struct Search {
+    strategy: PruneStrategy,
+
+    pending: VecDeque<Vec<FeatureName>>,
+    running: usize,
+    has_started: bool,
+
+    removed: Vec<FeatureName>,
+    kept: Vec<FeatureName>,
+
+    // only used by ddmin
+    candidates: Vec<FeatureName>,
+    granularity: usize,
+    has_round_progress: bool,
+}
+
+impl Search {
+    /// `removed` are already known to be removable - e.g. from a resumed run - and `kept` are already known to be required
+    pub fn new(
+        strategy: PruneStrategy,
+        features: &[FeatureName],
+        removed: Vec<FeatureName>,
+        kept: Vec<FeatureName>,
+    ) -> Self {
+        let candidates = features
+            .iter()
+            .filter(|feature| removed.contains(feature).not())
+            .filter(|feature| kept.contains(feature).not())
+            .cloned()
+            .collect_vec();
+
+        let pending = match strategy {
+            PruneStrategy::Linear => candidates
+                .iter()
+                .map(|feature| vec![feature.clone()])
+                .collect(),
+            PruneStrategy::Bisect | PruneStrategy::Ddmin if candidates.is_empty() => {
+                VecDeque::new()
+            }
+            PruneStrategy::Bisect | PruneStrategy::Ddmin => VecDeque::from([candidates.clone()]),
+        };
+
+        Self {
+            strategy,
+            pending,
+            running: 0,
+            has_started: false,
+            // ddmin keeps them disabled while testing the remaining candidates
+            removed,
+            kept,
+            candidates,
+            granularity: 1,
+            has_round_progress: false,
+        }
+    }
+
+    /// features which were proven to be required
+    pub fn kept(&self) -> &Vec<FeatureName> {
+        &self.kept
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.has_started
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty() && self.running == 0 && self.candidates_left().not()
+    }
+
+    fn candidates_left(&self) -> bool {
+        matches!(self.strategy, PruneStrategy::Ddmin) && self.candidates.is_empty().not()
+    }
+
+    /// returns the next test or none if there is currently nothing to test
+    ///
+    /// `decided` are features which do not need to be tested anymore
+    pub fn next_test(&mut self, decided: &[FeatureName]) -> Option<Test> {
+        // ddmin builds on previous results so only one test can run at a time
+        if matches!(self.strategy, PruneStrategy::Ddmin) && self.running > 0 {
+            return None;
+        }
+
+        loop {
+            if self.pending.is_empty() && self.running == 0 {
+                self.next_round(decided);
+            }
+
+            let features = self
+                .pending
+                .pop_front()?
+                .into_iter()
+                .filter(|feature| decided.contains(feature).not())
+                .filter(|feature| self.removed.contains(feature).not())
+                .collect_vec();
+
+            if features.is_empty() {
+                continue;
+            }
+
+            self.running += 1;
+            self.has_started = true;
+
+            let base = match self.strategy {
+                PruneStrategy::Ddmin => self.removed.clone(),
+                PruneStrategy::Linear | PruneStrategy::Bisect => vec![],
+            };
+
+            return Some(Test { features, base });
+        }
+    }
+
+    pub fn finish_test(&mut self, test: &Test, is_removable: bool) {
+        self.running -= 1;
+
+        if is_removable {
+            self.removed.extend(test.features.iter().cloned());
+            self.candidates
+                .retain(|feature| test.features.contains(feature).not());
+            self.has_round_progress = true;
+            return;
+        }
+
+        match self.strategy {
+            PruneStrategy::Linear => self.kept.extend(test.features.iter().cloned()),
+            PruneStrategy::Bisect => {
+                if test.features.len() == 1 {
+                    self.kept.extend(test.features.iter().cloned());
+                } else {
+                    let (first, second) = test.features.split_at(test.features.len() / 2);
+
+                    self.pending.push_front(second.to_vec());
+                    self.pending.push_front(first.to_vec());
+                }
+            }
+            // handled once the round is over
+            PruneStrategy::Ddmin => {}
+        }
+    }
+
+    /// splits the remaining ddmin candidates into chunks for the next round
+    fn next_round(&mut self, decided: &[FeatureName]) {
+        if matches!(self.strategy, PruneStrategy::Ddmin).not() {
+            return;
+        }
+
+        self.candidates
+            .retain(|feature| decided.contains(feature).not());
+
+        if self.candidates.is_empty() {
+            return;
+        }
+
+        if self.has_round_progress.not() {
+            if self.granularity >= self.candidates.len() {
+                self.kept.append(&mut self.candidates);
+                return;
+            }
+
+            self.granularity = (self.granularity * 2).min(self.candidates.len());
+        }
+
+        self.granularity = self.granularity.min(self.candidates.len());
+        self.has_round_progress = false;
+
+        let chunk_size = self.candidates.len().div_ceil(self.granularity);
+
+        self.pending = self
+            .candidates
+            .chunks(chunk_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::PruneStrategy;
+    use crate::prune::strategy::Search;
+    use itertools::Itertools;
+
+    /// runs the search with an oracle which fails if any required feature is disabled
+    fn run(strategy: PruneStrategy, features: &[&str], required: &[&str]) -> (Vec<String>, usize) {
+        let features = features.iter().map(|name| name.to_string()).collect_vec();
+
+        let mut search = Search::new(strategy, &features, vec![], vec![]);
+        let mut test_count = 0;
+
+        while let Some(test) = search.next_test(&[]) {
+            test_count += 1;
+
+            let is_removable = test
+                .features
+                .iter()
+                .chain(test.base.iter())
+                .all(|feature| !required.contains(&feature.as_str()));
+
+            search.finish_test(&test, is_removable);
+        }
+
+        assert!(search.is_finished());
+
+        let removed = features
+            .into_iter()
+            .filter(|feature| !search.kept().contains(feature))
+            .sorted()
+            .collect();
+
+        (removed, test_count)
+    }
+
+    #[test]
+    fn linear_tests_every_feature() {
+        let (removed, test_count) = run(PruneStrategy::Linear, &["a", "b", "c"], &["b"]);
+
+        assert_eq!(removed, vec!["a", "c"]);
+        assert_eq!(test_count, 3);
+    }
+
+    #[test]
+    fn bisect_needs_one_test_if_everything_is_removable() {
+        let features = (0..15).map(|i| i.to_string()).collect_vec();
+        let features = features.iter().map(|name| name.as_str()).collect_vec();
+
+        let (removed, test_count) = run(PruneStrategy::Bisect, &features, &[]);
+
+        assert_eq!(removed.len(), 15);
+        assert_eq!(test_count, 1);
+    }
+
+    #[test]
+    fn bisect_finds_required_features() {
+        let (removed, _) = run(
+            PruneStrategy::Bisect,
+            &["a", "b", "c", "d", "e", "f"],
+            &["b", "e"],
+        );
+
+        assert_eq!(removed, vec!["a", "c", "d", "f"]);
+    }
+
+    #[test]
+    fn ddmin_finds_required_features() {
+        let (removed, _) = run(
+            PruneStrategy::Ddmin,
+            &["a", "b", "c", "d", "e", "f", "g"],
+            &["a", "g"],
+        );
+
+        assert_eq!(removed, vec!["b", "c", "d", "e", "f"]);
+    }
+
+    #[test]
+    fn resumed_ddmin_keeps_removed_features_disabled() {
+        let features = ["a", "b", "c"].map(|name| name.to_string());
+
+        let mut search = Search::new(
+            PruneStrategy::Ddmin,
+            &features,
+            vec!["a".to_string()],
+            vec![],
+        );
+
+        let test = search.next_test(&[]).expect("nothing to test");
+
+        assert_eq!(test.features, ["b", "c"]);
+        assert_eq!(test.base, ["a"]);
+    }
+}
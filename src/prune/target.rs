@@ -0,0 +1,81 @@
+use crate::project::dependency::Dependency;
+use cargo_metadata::cargo_platform::Cfg;
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, bail};
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+/// a target triple the checks are run for
+pub struct Target {
+    pub triple: String,
+    /// only given targets get passed to cargo - otherwise cargo builds for the host as usual
+    pub is_explicit: bool,
+    pub is_host: bool,
+    cfg: Vec<Cfg>,
+}
+
+impl Target {
+    pub fn host() -> Result<Self> {
+        let triple = host_triple()?;
+
+        Ok(Self {
+            cfg: target_cfg(&triple)?,
+            triple,
+            is_explicit: false,
+            is_host: true,
+        })
+    }
+
+    /// fails if the std component of the target is not installed
+    pub fn new(triple: &str) -> Result<Self> {
+        let libdir = rustc(&["--print", "target-libdir", "--target", triple])
+            .or_else(|_| bail!("unknown target \"{triple}\""))?;
+
+        if !Path::new(libdir.trim()).is_dir() {
+            bail!(
+                "the std component for \"{triple}\" is not installed - try `rustup target add {triple}`"
+            );
+        }
+
+        Ok(Self {
+            triple: triple.to_string(),
+            is_explicit: true,
+            is_host: triple == host_triple()?,
+            cfg: target_cfg(triple)?,
+        })
+    }
+
+    /// returns false for dependencies which are only used on other platforms
+    pub fn applies_to(&self, dependency: &Dependency) -> bool {
+        dependency
+            .target
+            .as_ref()
+            .is_none_or(|platform| platform.matches(&self.triple, &self.cfg))
+    }
+}
+
+fn host_triple() -> Result<String> {
+    rustc(&["-vV"])?
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|triple| triple.trim().to_string())
+        .context("could not find the host target")
+}
+
+fn target_cfg(triple: &str) -> Result<Vec<Cfg>> {
+    rustc(&["--print", "cfg", "--target", triple])?
+        .lines()
+        .map(|line| Ok(Cfg::from_str(line)?))
+        .collect()
+}
+
+fn rustc(args: &[&str]) -> Result<String> {
+    let output = Command::new("rustc").args(args).output()?;
+
+    if !output.status.success() {
+        bail!("`rustc {}` failed", args.join(" "));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
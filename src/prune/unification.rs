@@ -0,0 +1,363 @@
+use crate::project::document::Document;
+use crate::prune::parse::remove_feature;
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName};
+use cargo_metadata::{Node, Package};
+use color_eyre::Result;
+use console::{Term, style};
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::ops::Not;
+use std::time::Duration;
+
+/// the crates which enable a feature
+pub type Dependents = BTreeSet<String>;
+
+/// features of direct dependencies which other crates in the dependency graph enable as well
+///
+/// cargo unifies the features of every use of a crate - disabling them in the Cargo.toml has no effect
+#[derive(Default)]
+pub struct UnifiedFeatures {
+    features: HashMap<(PackageName, DependencyName), BTreeMap<FeatureName, Dependents>>,
+}
+
+impl UnifiedFeatures {
+    /// checks the features to test against the resolve graph of `cargo metadata`
+    pub fn find(document: &Document, features_to_test: &FeaturesMap) -> Result<Self> {
+        let progress = ProgressBar::new_spinner()
+            .with_message(format!("running '{}'", style("cargo metadata").dim()));
+        progress.enable_steady_tick(Duration::from_millis(100));
+
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .current_dir(document.root_path())
+            .exec()?;
+
+        progress.finish_and_clear();
+
+        let Some(resolve) = &metadata.resolve else {
+            return Ok(Self::default());
+        };
+
+        let packages: HashMap<_, _> = metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package))
+            .collect();
+
+        let mut features = HashMap::new();
+
+        for (package_name, dependencies) in features_to_test {
+            let package = document.get_package(package_name)?;
+
+            for (dependency_name, tested_features) in dependencies {
+                let dependency = package.get_dep(dependency_name)?;
+
+                let Some(resolved) = metadata.packages.iter().find(|resolved| {
+                    resolved.name.as_str() == dependency.name
+                        && dependency.resolved_version.as_ref()
+                            == Some(&resolved.version.to_string())
+                }) else {
+                    continue;
+                };
+
+                let mut unified: BTreeMap<FeatureName, Dependents> = BTreeMap::new();
+
+                // workspace members are pruned themselves
+                let dependents = resolve
+                    .nodes
+                    .iter()
+                    .filter(|node| metadata.workspace_members.contains(&node.id).not())
+                    .filter(|node| node.deps.iter().any(|dep| dep.pkg == resolved.id))
+                    .filter_map(|node| packages.get(&node.id).map(|package| (node, *package)));
+
+                for (node, dependent) in dependents {
+                    for feature in enabled_features(dependent, node, resolved) {
+                        for feature in feature_closure(resolved, feature) {
+                            if tested_features.contains(&feature) {
+                                unified
+                                    .entry(feature)
+                                    .or_default()
+                                    .insert(dependent.name.to_string());
+                            }
+                        }
+                    }
+                }
+
+                if unified.is_empty().not() {
+                    features.insert((package_name.clone(), dependency_name.clone()), unified);
+                }
+            }
+        }
+
+        Ok(Self { features })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &PackageName,
+            &DependencyName,
+            &BTreeMap<FeatureName, Dependents>,
+        ),
+    > {
+        self.features
+            .iter()
+            .map(|((package_name, dependency_name), features)| {
+                (package_name, dependency_name, features)
+            })
+    }
+
+    /// unified features are not tested - together with the features they need
+    pub fn apply(&self, document: &Document, features_to_test: &mut FeaturesMap) -> Result<()> {
+        for (package_name, dependency_name, unified) in self.iter() {
+            let Some(features) = features_to_test
+                .get_mut(package_name)
+                .and_then(|dependencies| dependencies.get_mut(dependency_name))
+            else {
+                continue;
+            };
+
+            let dependency = document
+                .get_package(package_name)?
+                .get_dep(dependency_name)?;
+
+            for feature in unified.keys() {
+                remove_feature(feature, features, dependency);
+            }
+        }
+
+        for dependencies in features_to_test.values_mut() {
+            dependencies.retain(|_, features| features.is_empty().not());
+        }
+
+        features_to_test.retain(|_, dependencies| dependencies.is_empty().not());
+
+        Ok(())
+    }
+}
+
+/// the features `dependent` enables on `dependency` - directly or through its own enabled features
+fn enabled_features(dependent: &Package, node: &Node, dependency: &Package) -> Vec<FeatureName> {
+    let mut features = vec![];
+
+    // e.g. dev-dependencies of other crates are not part of the build
+    let kinds = node
+        .deps
+        .iter()
+        .filter(|dep| dep.pkg == dependency.id)
+        .flat_map(|dep| dep.dep_kinds.iter().map(|info| info.kind))
+        .collect_vec();
+
+    for declaration in dependent.dependencies.iter().filter(|declaration| {
+        declaration.name == dependency.name.as_str()
+            && declaration.req.matches(&dependency.version)
+            && kinds.contains(&declaration.kind)
+    }) {
+        features.extend(declaration.features.iter().cloned());
+
+        if declaration.uses_default_features {
+            features.push("default".to_string());
+        }
+
+        let key = declaration.rename.as_ref().unwrap_or(&declaration.name);
+
+        // e.g. `std = ["serde/std"]` or `std = ["serde?/std"]`
+        for enabled in &node.features {
+            for entry in dependent
+                .features
+                .get(enabled.as_str())
+                .into_iter()
+                .flatten()
+            {
+                if let Some((name, feature)) = entry.split_once('/')
+                    && name.trim_end_matches('?') == key
+                {
+                    features.push(feature.to_string());
+                }
+            }
+        }
+    }
+
+    features
+}
+
+/// the feature and all features it enables of the same crate
+fn feature_closure(package: &Package, feature: FeatureName) -> Vec<FeatureName> {
+    let mut closure = vec![];
+    let mut queue = vec![feature];
+
+    while let Some(feature) = queue.pop() {
+        if closure.contains(&feature) {
+            continue;
+        }
+
+        for entry in package.features.get(&feature).into_iter().flatten() {
+            if entry.contains([':', '/']).not() {
+                queue.push(entry.clone());
+            }
+        }
+
+        closure.push(feature);
+    }
+
+    closure
+}
+
+pub fn display_unified_features(unified_features: &UnifiedFeatures) -> Result<()> {
+    let term = Term::stdout();
+
+    let entries = unified_features
+        .iter()
+        .flat_map(|(package_name, dependency_name, features)| {
+            features.iter().map(move |(feature, dependents)| {
+                (package_name, dependency_name, feature, dependents)
+            })
+        })
+        .sorted()
+        .collect_vec();
+
+    writeln!(
+        &term,
+        "features enabled by other dependencies [{}]",
+        entries.len()
+    )?;
+
+    for (package_name, dependency_name, feature, dependents) in entries {
+        writeln!(
+            &term,
+            "  {} {}/{} {}",
+            package_name,
+            dependency_name,
+            feature,
+            style(format!("(by {})", dependents.iter().join(", "))).color256(8)
+        )?;
+    }
+
+    writeln!(&term)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::project::document::Document;
+    use crate::project::fixture::project;
+    use crate::prune::unification::UnifiedFeatures;
+    use std::collections::{BTreeSet, HashMap};
+
+    fn manifest(name: &str, rest: &str) -> String {
+        format!(
+            r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2024"
+
+{rest}
+"#
+        )
+    }
+
+    #[test]
+    fn features_of_other_crates_are_not_tested() {
+        let dir = project(&[
+            (
+                "app/Cargo.toml",
+                &manifest(
+                    "app",
+                    r#"[dependencies]
+dep = { path = "../dep", features = ["a", "b"] }
+other = { path = "../other" }"#,
+                ),
+            ),
+            ("app/src/lib.rs", ""),
+            (
+                "other/Cargo.toml",
+                &manifest(
+                    "other",
+                    r#"[dependencies]
+dep = { path = "../dep", features = ["a"] }"#,
+                ),
+            ),
+            ("other/src/lib.rs", ""),
+            (
+                "dep/Cargo.toml",
+                &manifest(
+                    "dep",
+                    r#"[features]
+a = ["c"]
+b = []
+c = []"#,
+                ),
+            ),
+            ("dep/src/lib.rs", ""),
+        ]);
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let mut features_to_test = HashMap::from([(
+            "app".to_string(),
+            HashMap::from([(
+                "dep".to_string(),
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            )]),
+        )]);
+
+        let unified = UnifiedFeatures::find(&document, &features_to_test).unwrap();
+
+        // `other` is not a member of the workspace and enables `a` which enables `c`
+        let entries = unified.iter().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+
+        let (_, _, features) = entries[0];
+        assert_eq!(features.keys().collect::<Vec<_>>(), ["a", "c"]);
+        assert_eq!(features["a"], BTreeSet::from(["other".to_string()]));
+
+        unified.apply(&document, &mut features_to_test).unwrap();
+
+        assert_eq!(features_to_test["app"]["dep"], ["b"]);
+    }
+
+    #[test]
+    fn dependencies_without_tested_features_are_removed() {
+        let dir = project(&[
+            (
+                "app/Cargo.toml",
+                &manifest(
+                    "app",
+                    r#"[dependencies]
+dep = { path = "../dep", features = ["a"] }
+other = { path = "../other" }"#,
+                ),
+            ),
+            ("app/src/lib.rs", ""),
+            (
+                "other/Cargo.toml",
+                &manifest(
+                    "other",
+                    r#"[dependencies]
+dep = { path = "../dep", features = ["a"] }"#,
+                ),
+            ),
+            ("other/src/lib.rs", ""),
+            ("dep/Cargo.toml", &manifest("dep", "[features]\na = []")),
+            ("dep/src/lib.rs", ""),
+        ]);
+        let document = Document::new(dir.path().join("app")).unwrap();
+
+        let mut features_to_test = HashMap::from([(
+            "app".to_string(),
+            HashMap::from([("dep".to_string(), vec!["a".to_string()])]),
+        )]);
+
+        let unified = UnifiedFeatures::find(&document, &features_to_test).unwrap();
+        unified.apply(&document, &mut features_to_test).unwrap();
+
+        assert!(features_to_test.is_empty());
+    }
+}
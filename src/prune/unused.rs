@@ -0,0 +1,126 @@
+use crate::io::save::remove_dependency;
+use crate::project::dependency::DependencyType;
+use crate::project::document::Document;
+use crate::prune::check::Checker;
+use crate::prune::filter::Filter;
+use crate::prune::{DependencyName, PackageName};
+use color_eyre::Result;
+use console::{Term, style};
+use indicatif::ProgressBar;
+use std::fs;
+use std::io::Write;
+use std::ops::Not;
+use std::path::Path;
+use std::time::Duration;
+
+/// a dependency the project still works without
+pub struct UnusedDependency {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+}
+
+/// removes every dependency on its own from the project and checks if it still works
+///
+/// returns the unused dependencies and the names of the skipped `[workspace.dependencies]`
+pub fn find_unused_dependencies(
+    document: &Document,
+    checker: &Checker,
+    filter: &Filter,
+    target_dir: Option<&Path>,
+) -> Result<(Vec<UnusedDependency>, Vec<DependencyName>)> {
+    let progress = ProgressBar::new_spinner();
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    let lock_path = document.root_path().join("Cargo.lock");
+    let mut unused_dependencies = vec![];
+    let mut skipped_dependencies = vec![];
+
+    for package in document.get_packages() {
+        let package_name = document.plain_package_name(&package.name)?;
+
+        for dependency in package.get_deps() {
+            if filter.matches(&package_name, dependency).not() {
+                continue;
+            }
+
+            // workspace dependencies are only removed together with all members using them
+            if matches!(dependency.kind, DependencyType::Workspace) {
+                skipped_dependencies.push(dependency.name.clone());
+                continue;
+            }
+
+            if checker
+                .targets()
+                .iter()
+                .all(|target| !target.applies_to(dependency))
+            {
+                continue;
+            }
+
+            progress.set_message(format!(
+                "checking if {} uses {}",
+                package.name,
+                dependency.get_name()
+            ));
+
+            let manifest = fs::read_to_string(&package.manifest_path)?;
+            let lock = fs::read(&lock_path).ok();
+
+            remove_dependency(document, &package.name, &dependency.get_name())?;
+
+            let outcome = checker.check(document.root_path(), target_dir);
+
+            //reset to start
+            fs::write(&package.manifest_path, manifest)?;
+
+            if let Some(lock) = lock {
+                fs::write(&lock_path, lock)?;
+            }
+
+            if outcome?.is_success() {
+                unused_dependencies.push(UnusedDependency {
+                    package_name: package.name.clone(),
+                    dependency_name: dependency.get_name(),
+                });
+            }
+        }
+    }
+
+    progress.finish_and_clear();
+
+    Ok((unused_dependencies, skipped_dependencies))
+}
+
+pub fn display_unused_dependencies(
+    unused_dependencies: &[UnusedDependency],
+    skipped_dependencies: &[DependencyName],
+) -> Result<()> {
+    let term = Term::stdout();
+
+    writeln!(&term, "unused dependencies [{}]", unused_dependencies.len())?;
+
+    for unused in unused_dependencies {
+        writeln!(
+            &term,
+            "  {} [{}]",
+            unused.package_name,
+            style(format!("-{}", unused.dependency_name)).red()
+        )?;
+    }
+
+    if skipped_dependencies.is_empty().not() {
+        writeln!(
+            &term,
+            "  {}",
+            style(format!(
+                "└ not checked in [workspace.dependencies] - remove them once no member uses them: {}",
+                skipped_dependencies.join(", ")
+            ))
+            .color256(8)
+        )?;
+    }
+
+    writeln!(&term)?;
+
+    Ok(())
+}
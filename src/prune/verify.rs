@@ -0,0 +1,379 @@
+use crate::io::backup::ManifestBackup;
+use crate::io::save::{remove_dependency, save_dependency};
+use crate::project::document::Document;
+use crate::prune::check::{CheckOutcome, Checker};
+use crate::prune::scheduler::Job;
+use crate::prune::unused::UnusedDependency;
+use crate::prune::{DependencyName, FeatureName, PackageName};
+use color_eyre::Result;
+use color_eyre::eyre::bail;
+use console::{Term, style};
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use std::io::Write;
+use std::ops::Not;
+use std::path::Path;
+use std::time::Duration;
+
+/// a removal found by checking it on its own
+#[derive(Clone, Debug, PartialEq)]
+enum Removal {
+    /// a removable feature of a job
+    Feature { job: usize, feature: FeatureName },
+    /// an unused dependency
+    Dependency { unused: usize },
+}
+
+/// a removal which breaks the project together with the other removals
+pub struct Conflict {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    /// none if the dependency is unused on its own
+    pub feature: Option<FeatureName>,
+    pub reason: String,
+}
+
+/// checks the project with every removable feature disabled and every unused dependency removed at once
+///
+/// if that fails the removals are bisected to find the ones which break the combination - they are kept instead
+///
+/// runs even if the budget ran out - the removals of the checked features are only applied once they are verified
+pub fn verify_removals(
+    document: &mut Document,
+    checker: &Checker,
+    target_dir: Option<&Path>,
+    jobs: &mut [Job],
+    unused_dependencies: &mut Vec<UnusedDependency>,
+) -> Result<Vec<Conflict>> {
+    let removals = all_removals(jobs, unused_dependencies);
+
+    if removals.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let progress = ProgressBar::new_spinner().with_message("verifying all removals together");
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    let conflicts = find_conflicts(removals, &progress, |removals| {
+        check_removals(
+            document,
+            checker,
+            target_dir,
+            jobs,
+            unused_dependencies,
+            removals,
+        )
+    });
+
+    progress.finish_and_clear();
+
+    let mut kept_dependencies = vec![];
+
+    let conflicts = conflicts?
+        .into_iter()
+        .map(|(removal, reason)| match removal {
+            Removal::Feature { job, feature } => {
+                let job = &mut jobs[job];
+
+                job.keep_conflicting(feature.clone(), reason.clone());
+
+                Conflict {
+                    package_name: job.package_name.clone(),
+                    dependency_name: job.dependency_name.clone(),
+                    feature: Some(feature),
+                    reason,
+                }
+            }
+            Removal::Dependency { unused } => {
+                kept_dependencies.push(unused);
+
+                let unused = &unused_dependencies[unused];
+
+                Conflict {
+                    package_name: unused.package_name.clone(),
+                    dependency_name: unused.dependency_name.clone(),
+                    feature: None,
+                    reason,
+                }
+            }
+        })
+        .collect();
+
+    let mut index = 0;
+    unused_dependencies.retain(|_| {
+        index += 1;
+        kept_dependencies.contains(&(index - 1)).not()
+    });
+
+    Ok(conflicts)
+}
+
+/// the unused dependencies first - a removed dependency saves more than a disabled feature
+fn all_removals(jobs: &[Job], unused_dependencies: &[UnusedDependency]) -> Vec<Removal> {
+    let dependencies = (0..unused_dependencies.len()).map(|unused| Removal::Dependency { unused });
+
+    let features = jobs.iter().enumerate().flat_map(|(index, job)| {
+        job.removable_features()
+            .into_iter()
+            .map(move |feature| Removal::Feature {
+                job: index,
+                feature,
+            })
+    });
+
+    dependencies.chain(features).collect()
+}
+
+/// removes the conflicting removals until the check succeeds - returns them with the reason of the failure
+fn find_conflicts<T>(
+    mut remaining: Vec<T>,
+    progress: &ProgressBar,
+    mut check: impl FnMut(&[T]) -> Result<CheckOutcome>,
+) -> Result<Vec<(T, String)>> {
+    let mut conflicts = vec![];
+
+    loop {
+        let CheckOutcome::Failure(mut reason) = check(&remaining)? else {
+            break;
+        };
+
+        if remaining.is_empty() {
+            bail!("the checks fail without any removals: {reason}");
+        }
+
+        // the shortest failing prefix ends with a conflicting removal
+        let (mut passing, mut failing) = (0, remaining.len());
+
+        while failing - passing > 1 {
+            let middle = (passing + failing) / 2;
+
+            progress.set_message(format!(
+                "bisecting conflicting removals [{}]",
+                failing - passing
+            ));
+
+            match check(&remaining[..middle])? {
+                CheckOutcome::Success => passing = middle,
+                CheckOutcome::Failure(failure) => {
+                    failing = middle;
+                    reason = failure;
+                }
+            }
+        }
+
+        conflicts.push((remaining.remove(failing - 1), reason));
+    }
+
+    Ok(conflicts)
+}
+
+/// applies the removals while the checks run and restores the manifests afterwards
+fn check_removals(
+    document: &mut Document,
+    checker: &Checker,
+    target_dir: Option<&Path>,
+    jobs: &[Job],
+    unused_dependencies: &[UnusedDependency],
+    removals: &[Removal],
+) -> Result<CheckOutcome> {
+    let backup = ManifestBackup::new(document)?;
+
+    let mut features = vec![];
+    let mut dependencies = vec![];
+
+    for removal in removals {
+        match removal {
+            Removal::Feature { job, feature } => features.push((&jobs[*job], feature)),
+            Removal::Dependency { unused } => dependencies.push(&unused_dependencies[*unused]),
+        }
+    }
+
+    let changed_dependencies = features
+        .iter()
+        .map(|(job, _)| (&job.package_name, &job.dependency_name))
+        .dedup()
+        .collect_vec();
+
+    let mut enabled_features = vec![];
+
+    for (package_name, dependency_name) in &changed_dependencies {
+        let dependency = document
+            .get_package(package_name)?
+            .get_dep(dependency_name)?;
+
+        enabled_features.push(dependency.get_enabled_features());
+    }
+
+    for (job, feature) in &features {
+        document
+            .get_package_mut(&job.package_name)?
+            .get_dep_mut(&job.dependency_name)?
+            .disable_feature(feature)?;
+    }
+
+    for (package_name, dependency_name) in &changed_dependencies {
+        save_dependency(document, package_name, dependency_name)?;
+    }
+
+    for unused in dependencies {
+        remove_dependency(document, &unused.package_name, &unused.dependency_name)?;
+    }
+
+    let outcome = checker.check(document.root_path(), target_dir);
+
+    //reset to start - the files are restored by the backup
+    for ((package_name, dependency_name), features) in
+        changed_dependencies.iter().zip(enabled_features)
+    {
+        let dependency = document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?;
+
+        for feature in &features {
+            dependency.enable_feature(feature)?;
+        }
+    }
+
+    drop(backup);
+
+    outcome
+}
+
+pub fn display_conflicts(conflicts: &[Conflict]) -> Result<()> {
+    let term = Term::stdout();
+
+    writeln!(
+        &term,
+        "removals which conflict with each other [{}]",
+        conflicts.len()
+    )?;
+
+    for conflict in conflicts {
+        match &conflict.feature {
+            Some(feature) => writeln!(
+                &term,
+                "  {} {}/{}",
+                conflict.package_name, conflict.dependency_name, feature
+            )?,
+            None => writeln!(
+                &term,
+                "  {} {} (unused)",
+                conflict.package_name, conflict.dependency_name
+            )?,
+        }
+
+        writeln!(
+            &term,
+            "    {}",
+            style(format!("└ kept: {}", conflict.reason)).color256(8)
+        )?;
+    }
+
+    writeln!(&term)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use crate::prune::check::CheckOutcome;
+    use crate::prune::unused::UnusedDependency;
+    use crate::prune::verify::{Removal, all_removals, find_conflicts};
+    use indicatif::ProgressBar;
+
+    /// fails if `a` and `b` or `c` and `d` are removed together
+    fn oracle(removals: &[char]) -> CheckOutcome {
+        for (first, second) in [('a', 'b'), ('c', 'd')] {
+            if removals.contains(&first) && removals.contains(&second) {
+                return CheckOutcome::Failure(format!("{first}{second}"));
+            }
+        }
+
+        CheckOutcome::Success
+    }
+
+    #[test]
+    fn conflicting_removals_are_found() {
+        let mut check_count = 0;
+
+        let conflicts = find_conflicts(
+            vec!['a', 'x', 'c', 'b', 'y', 'd', 'z'],
+            &ProgressBar::hidden(),
+            |removals| {
+                check_count += 1;
+                Ok(oracle(removals))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            conflicts,
+            vec![('b', "ab".to_string()), ('d', "cd".to_string())]
+        );
+        // 3 bisection steps per conflict and one check of every combination
+        assert_eq!(check_count, 9);
+    }
+
+    #[test]
+    fn compatible_removals_are_checked_once() {
+        let mut check_count = 0;
+
+        let conflicts = find_conflicts(vec!['a', 'c', 'x'], &ProgressBar::hidden(), |removals| {
+            check_count += 1;
+            Ok(oracle(removals))
+        })
+        .unwrap();
+
+        assert!(conflicts.is_empty());
+        assert_eq!(check_count, 1);
+    }
+
+    #[test]
+    fn failing_baseline_is_an_error() {
+        let result = find_conflicts(vec!['x'], &ProgressBar::hidden(), |_| {
+            Ok(CheckOutcome::Failure("broken".to_string()))
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unused_dependencies_are_verified_together() {
+        let unused_dependencies = ["a", "b", "c"]
+            .map(|name| UnusedDependency {
+                package_name: "app".to_string(),
+                dependency_name: name.to_string(),
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        // there are no removable features
+        let removals = all_removals(&[], &unused_dependencies);
+        assert_eq!(removals.len(), 3);
+
+        // `a` and `c` are each unused on their own - e.g. both provide the same trait impl
+        let conflicts = find_conflicts(removals, &ProgressBar::hidden(), |removals| {
+            let is_removed = |name: &str| {
+                removals.iter().any(|removal| {
+                    matches!(removal, Removal::Dependency { unused } if unused_dependencies[*unused].dependency_name == name)
+                })
+            };
+
+            Ok(if is_removed("a") && is_removed("c") {
+                CheckOutcome::Failure("error[E0277]".to_string())
+            } else {
+                CheckOutcome::Success
+            })
+        })
+        .unwrap();
+
+        assert_eq!(
+            conflicts,
+            vec![(
+                Removal::Dependency { unused: 2 },
+                "error[E0277]".to_string()
+            )]
+        );
+    }
+}
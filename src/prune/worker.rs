@@ -0,0 +1,120 @@
+use crate::CleanLevel;
+use crate::project::document::Document;
+use crate::prune::FeatureName;
+use crate::prune::check::{CheckOutcome, Checker, clean};
+use crate::prune::scheduler::{SharedScheduler, Task};
+use crate::prune::with_features_disabled;
+use color_eyre::Result;
+use itertools::Itertools;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+pub enum WorkerEvent {
+    Started {
+        worker: usize,
+        job: usize,
+        features: Vec<FeatureName>,
+    },
+    Finished {
+        worker: usize,
+    },
+}
+
+/// checks features inside its own copy of the project
+pub struct Worker<'a> {
+    pub id: usize,
+    pub document: &'a mut Document,
+    pub checker: &'a Checker,
+    pub clean_level: CleanLevel,
+    /// the persistent `CARGO_TARGET_DIR` of this worker
+    pub target_dir: Option<PathBuf>,
+}
+
+impl Worker<'_> {
+    pub fn run(mut self, scheduler: &SharedScheduler, sender: Sender<WorkerEvent>) -> Result<()> {
+        let result = self.work(scheduler, sender);
+
+        if result.is_err() {
+            scheduler.abort();
+        }
+
+        result
+    }
+
+    fn work(&mut self, scheduler: &SharedScheduler, sender: Sender<WorkerEvent>) -> Result<()> {
+        let mut last_task: Option<Task> = None;
+
+        loop {
+            let task = scheduler.wait_for_task();
+
+            if let Some(last_task) = &last_task {
+                self.clean_if_needed(last_task, task.as_ref())?;
+            }
+
+            let Some(task) = task else {
+                return Ok(());
+            };
+
+            let _ = sender.send(WorkerEvent::Started {
+                worker: self.id,
+                job: task.job,
+                features: task.test.features.clone(),
+            });
+
+            let start = Instant::now();
+            let outcome = self.check_test(&task)?;
+            let duration = start.elapsed();
+
+            let dependency = self
+                .document
+                .get_package(&task.package_name)?
+                .get_dep(&task.dependency_name)?;
+
+            scheduler.finish_task(&task, &outcome, duration, dependency);
+
+            let _ = sender.send(WorkerEvent::Finished { worker: self.id });
+
+            last_task = Some(task);
+        }
+    }
+
+    /// checks if the project still works without the features of the test
+    fn check_test(&mut self, task: &Task) -> Result<CheckOutcome> {
+        let features = task
+            .test
+            .base
+            .iter()
+            .chain(&task.test.features)
+            .cloned()
+            .collect_vec();
+
+        let checker = self.checker;
+        let target_dir = self.target_dir.as_deref();
+
+        with_features_disabled(
+            self.document,
+            &task.package_name,
+            &task.dependency_name,
+            &features,
+            |document| checker.check(document.root_path(), target_dir),
+        )
+    }
+
+    fn clean_if_needed(&self, last_task: &Task, next_task: Option<&Task>) -> Result<()> {
+        let is_next_package =
+            next_task.is_none_or(|task| task.package_name != last_task.package_name);
+        let is_next_dependency = is_next_package
+            || next_task.is_none_or(|task| task.dependency_name != last_task.dependency_name);
+
+        match self.clean_level {
+            CleanLevel::Package if is_next_package => {
+                clean(self.document.root_path(), self.target_dir.as_deref())
+            }
+            CleanLevel::Dependency if is_next_dependency => {
+                clean(self.document.root_path(), self.target_dir.as_deref())
+            }
+            _ => Ok(()),
+        }
+    }
+}